@@ -1,7 +1,7 @@
 use std::env;
 use std::fs::File;
 use std::io::{BufRead, BufReader, Write};
-use std::collections::VecDeque;
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
 use std::process;
 
 
@@ -9,13 +9,80 @@ use std::process;
 struct Process {
     name: String,       // process name
     pid: u32,       // process ID
+    tid: u32,       // thread ID (process의 main thread는 pid와 같음) - 스케줄링과 상태는 thread 단위
     ppid: u32,      // parent process ID
-    status: String,     // process status: S(sleeping) / W(waiting) / None
     lines: VecDeque<String>,     // process file의 명령어 저장 queue
+    priority: u32,      // 프로세스 우선순위 (클수록 높음)
+    start_cycle: u32,       // process가 생성된 cycle
+    code: Vec<String>,      // 프로그램 전체 (signal handler label을 찾을 때 사용)
+    pending: u64,       // 도착했지만 아직 전달되지 않은 signal (signal 번호 자리의 bit)
+}
+
+// process 상태 - process table(PROCS)에 저장되고 set_state로만 바뀜
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum State {
+    New,        // 생성되어 아직 ready queue에 들어가지 않음
+    Ready,      // ready queue에서 CPU를 기다림
+    Running,        // 실행 중
+    Blocked(Wait),      // 이벤트를 기다림 (waiting queue 또는 장치 wait queue)
+    SuspendedReady,     // suspend된 ready process (SR)
+    SuspendedBlocked(Wait),     // suspend된 blocked process (SB) - 기다리던 이벤트가 끝나면 SR
+    Zombie(i32),        // 종료되었지만 부모가 아직 exit status를 회수하지 않음
+    Terminated,     // 부모가 exit status를 회수함 -> 이번 cycle 출력 후 process table에서 제거
+}
+
+// blocked process가 기다리는 이벤트
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Wait {
+    Child,      // wait: 자식 process 종료
+    Disk,       // 디스크 읽기 완료
+    Device,     // 장치 I/O 완료 (interrupt)
+    Sleep,      // sleep timer 만료
+    Stopped,        // SIGSTOP으로 정지 -> SIGCONT가 오면 다시 ready
+    Join(u32),      // thread_join: 해당 tid의 thread 종료
+    Mutex(u32),     // mutex_lock: 해당 mutex가 풀림
+    Sem(u32),       // sem_wait: 해당 semaphore의 sem_post
+    Cond(u32),      // cond_wait: 해당 condition variable의 cond_signal
+    Resource,       // request: 요청한 자원 (어떤 자원인지는 RES_WAIT에 있음)
+    Lock(u32),      // lock: 해당 lock이 풀림
+}
+
+// process의 주소 공간 - 같은 process의 thread들은 하나의 주소 공간을 함께 사용 (SPACES에 pid로 저장)
+struct AddressSpace {
     virtual_mem: [Option<Page>;32],         // process의 가상 메모리
     page_table: [[i32;2];32],       // page table: (page id, frame id)를 요소로 가짐
     page_id: i32,       // 해당 프로세스에서 마지막으로 할당한 페이지 ID
     allocation_id: i32,         // 해당 프로세스에서 마지막으로 할당한 allocation ID
+    table_frame: [i32;PT_NODES],        // 다단계 페이지 테이블의 각 테이블 페이지가 위치한 물리메모리 index (없으면 -1)
+    ws_refs: VecDeque<(i32, bool)>,     // 최근 메모리 참조 기록 (page id, 페이지 폴트 여부) - working set window 크기만큼 유지
+}
+
+// process table 항목 - 메모리를 모두 해제한 zombie도 부모가 회수할 때까지 남음
+struct ProcEntry {
+    name: String,       // process name
+    ppid: u32,      // parent process ID
+    state: State,       // process 상태
+    tgid: u32,      // thread가 속한 process의 pid (process 자신이면 key와 같음)
+    orphan: bool,       // 부모가 먼저 종료되어 init에 입양됨 -> 종료하면 init이 바로 회수
+    fds: BTreeMap<u32, usize>,      // 파일 디스크립터 테이블: fd -> 열린 파일 테이블 index (thread는 속한 process의 항목을 사용)
+    handlers: BTreeMap<u32, String>,        // signal 번호 -> handler label (SIG_IGN이면 무시, thread는 속한 process의 항목을 사용)
+}
+
+#[derive(Clone)]
+struct Inode {
+    dir: bool,      // 디렉터리인지 (false면 일반 파일)
+    size: u32,      // 파일 크기 (블록 수)
+    links: u32,     // 이 inode를 가리키는 디렉터리 항목 수
+    entries: BTreeMap<String, u32>,     // 디렉터리일 경우 항목 (이름 -> inode 번호)
+}
+
+#[derive(Clone)]
+struct DiskRequest {
+    pid: u32,       // I/O 완료를 기다리는 process ID (0이면 기다리는 process 없음 - 비동기 쓰기)
+    read: bool,     // 읽기 요청인지 (false면 쓰기)
+    label: String,      // 출력용 페이지 이름
+    cylinder: u32,      // 요청한 블록이 위치한 실린더
+    submit: u32,        // 요청이 들어온 cycle
 }
 
 #[derive(Copy, Clone)]
@@ -23,8 +90,10 @@ struct Page {
     pid: u32,       // process ID
     page_id: i32,       
     allocation_id: i32,
-    authority: i32,         // 페이지 권한 - 0: 읽기,쓰기 모두 가능 / 1: 읽기만 가능
+    perms: u32,     // 페이지 접근 권한 비트 (PERM_R | PERM_W | PERM_X)
+    cow: bool,      // copy-on-write 공유 중인지 (쓰기 권한이 있어도 쓰기 시 복사 필요)
     count: u32,     // 페이지가 사용된 횟수 
+    table: i32,     // 페이지 테이블 페이지일 경우 테이블 노드 번호 / 일반 페이지는 -1
 }
 
 static mut CYCLE: u32 = 0;
@@ -33,40 +102,160 @@ static mut MODE: String = String::new();       // user or kernel
 static mut COMMAND: String = String::new();
 static mut RQ: VecDeque<Process> = VecDeque::new();       // ready queue
 static mut WQ: VecDeque<Process> = VecDeque::new();       // waiting queue
+static mut IO_QUEUES: BTreeMap<String, (VecDeque<(Process, u32)>, u32)> = BTreeMap::new();       // 장치별 wait queue: 장치 이름 -> ((process, I/O cycle 수) 큐, 맨 앞 process의 I/O가 끝나는 cycle)
+static mut INTERRUPT_EVENT: String = String::new();     // 이번 interrupt cycle에 처리한 장치와 깨운 process
+static mut TIMERS: BTreeSet<(u32, u32, bool)> = BTreeSet::new();        // timer queue: (만료 cycle, pid, alarm 여부) - alarm이 아니면 sleep 중인 process를 깨움
+static mut TIMER_EVENT: String = String::new();     // 이번 cycle에 만료된 timer와 timer 명령어 결과
+static mut SQ: VecDeque<Process> = VecDeque::new();       // suspended queue (페이지를 모두 내보내고 일시 중지된 process - SR 또는 SB 상태)
 static mut RUNNING: Option<Process> = None;        // 현재 실행 중인 process
 static mut NEWP: Option<Process> = None;       // 새로 들어온 process 
-static mut TERMINATED: Option<Process> = None;     // terminated 상태인 process 
+static mut PROCS: BTreeMap<u32, ProcEntry> = BTreeMap::new();      // process table: pid -> 모든 상태(new ~ terminated)의 process
+static mut SPACES: BTreeMap<u32, AddressSpace> = BTreeMap::new();       // 주소 공간: pid -> process의 주소 공간 (thread들은 tid가 아닌 pid로 찾아 공유)
+static mut REAP_EVENT: String = String::new();      // 이번 cycle에 회수된 zombie와 exit status
+static mut SIGNAL_EVENT: String = String::new();        // 이번 cycle의 signal 전송 / 전달 / handler 등록 결과
+static mut PSTREE: bool = false;        // 매 cycle process tree와 queue 내용을 출력할지
+static mut HISTORY: BTreeMap<u32, (u32, String)> = BTreeMap::new();        // 회수되어 process table에서 제거된 process: tid -> (parent ID, 출력 이름과 exit status) - summary의 process tree용
+static mut THREAD_EVENT: String = String::new();        // 이번 cycle의 thread 생성 / join 결과
+static mut MUTEXES: BTreeMap<u32, (Option<u32>, VecDeque<u32>)> = BTreeMap::new();       // mutex: id -> (lock을 가진 tid, lock을 기다리는 tid 큐)
+static mut SEMAPHORES: BTreeMap<u32, (u32, VecDeque<u32>)> = BTreeMap::new();        // semaphore: id -> (값, sem_wait에서 기다리는 tid 큐)
+static mut CONDS: BTreeMap<u32, VecDeque<(u32, u32)>> = BTreeMap::new();      // condition variable: id -> (기다리는 tid, 깨어나면 다시 잡을 mutex id) 큐
+static mut LOCKS: BTreeMap<u32, (Option<u32>, VecDeque<u32>)> = BTreeMap::new();      // lock: id -> (lock을 가진 tid, lock을 기다리는 tid 목록) - 우선순위가 높은 thread부터 넘겨받음
+static mut LOCK_PROTOCOL: String = String::new();       // lock의 우선순위 프로토콜 (inherit / ceiling, 없으면 사용 X)
+static mut CEILINGS: BTreeMap<u32, u32> = BTreeMap::new();      // lock id별 ceiling 우선순위 (지정되지 않으면 lock을 쓰는 process 중 가장 높은 우선순위)
+static mut SCHED: String = String::new();       // CPU 스케줄링 방식 (priority, 없으면 ready queue 순서대로)
+static mut SYNC_EVENT: String = String::new();      // 이번 cycle의 mutex / semaphore / condition variable 처리 결과
+static mut RESOURCES: BTreeMap<String, (u32, u32)> = BTreeMap::new();      // 자원 종류: 이름 -> (전체 개수, 남은 개수)
+static mut HELD: BTreeMap<u32, BTreeMap<String, u32>> = BTreeMap::new();       // tid -> (자원 이름 -> 가지고 있는 개수)
+static mut CLAIMS: BTreeMap<u32, BTreeMap<String, u32>> = BTreeMap::new();        // Banker's algorithm: tid -> (자원 이름 -> 프로그램 파일에 선언한 최대 요청 개수)
+static mut RES_WAIT: VecDeque<(u32, String, u32)> = VecDeque::new();      // 자원을 기다리는 (tid, 자원 이름, 개수) - 요청 순서대로
+static mut BANKER: bool = false;        // Banker's algorithm으로 안전한 요청만 들어줄지
+static mut RECOVERY: String = String::new();        // deadlock 회복 방법 (abort_one / abort_all / preempt, 없으면 보고만)
+static mut RESOURCE_EVENT: String = String::new();      // 이번 cycle의 자원 요청 / 반환 결과
+static mut DEADLOCK_EVENT: String = String::new();      // 이번 cycle에 deadlock 회복을 위해 한 일
+static mut DEADLOCKED: Vec<String> = Vec::new();       // 현재 deadlock에 빠진 thread들의 wait-for 관계 (scheduling할 때 검사)
+static mut RECOVERED: String = String::new();       // 이번 cycle에 회복하기 전 발견한 deadlock의 wait-for 관계
+static mut DEADLOCKS: Vec<String> = Vec::new();     // 발생한 deadlock과 회복 기록
+static mut REPARENT_EVENT: String = String::new();      // 이번 cycle에 init에 입양된 orphan process
 static NONE_PAGE: Option<Page> = None;
 static mut PHYSICAL_MEM: [Option<Page>;16] = [NONE_PAGE;16];        // 물리 메모리
+static mut RMAP: BTreeMap<(u32, i32), Vec<(u32, usize)>> = BTreeMap::new();       // 역매핑: 페이지 (pid, page id) -> 해당 페이지를 매핑하는 (프로세스 pid, 가상 메모리 index) 목록
+static mut PINNED: BTreeSet<(u32, usize)> = BTreeSet::new();        // memory_lock으로 고정된 매핑 (프로세스 pid, 가상 메모리 index) -> 이 매핑이 가리키는 페이지는 victim으로 선택되지 않음
+static mut SHM: BTreeMap<u32, (Vec<i32>, u32)> = BTreeMap::new();        // 공유 메모리 segment: key -> (페이지 id 목록, attach한 process 수)
+static mut SHARED_PAGE_ID: i32 = SHARED_PAGE_BASE - 1;        // 마지막으로 만든 공유 페이지(공유 메모리, 파일) ID
+static mut FILE_PAGES: BTreeMap<i32, (String, u32)> = BTreeMap::new();       // 파일 페이지: 페이지 ID -> (파일 이름, 블록 번호) - 물리메모리에 있는 파일 페이지가 page cache
+static mut DIRTY: BTreeSet<i32> = BTreeSet::new();      // 물리메모리에서 수정되어 파일에 다시 써야 하는 파일 페이지 ID
+static mut FILE_EVENT: String = String::new();      // 이번 cycle의 파일 읽기 / write-back 내용
+static mut FILE_STATS: (u32, u32, u32) = (0, 0, 0);     // page cache (hit 수, miss 수 - 파일에서 읽은 페이지 수, write-back한 페이지 수)
+static mut INODES: BTreeMap<u32, Inode> = BTreeMap::new();      // 시뮬레이션 파일시스템의 inode (0번은 루트 디렉터리)
+static mut NEXT_INO: u32 = 1;       // 다음에 만들 inode 번호
+static mut OPEN_FILES: BTreeMap<usize, (u32, String, u32, u32)> = BTreeMap::new();        // 열린 파일 테이블: index -> (inode 번호, 경로, offset, 참조하는 fd 수)
+static mut NEXT_OPEN: usize = 0;        // 다음 열린 파일 테이블 index
+static mut FS_IMAGE: String = String::new();        // 파일시스템을 불러오고 저장할 host 이미지 파일 경로 (없으면 저장 X)
+static mut FS_EVENT: String = String::new();        // 이번 cycle의 파일시스템 명령어 결과
+static mut DISK_SCHED: String = String::new();      // 디스크 스케줄링 알고리즘 (fcfs / sstf / scan / cscan / look, 없으면 디스크 모델 사용 X - I/O가 즉시 끝남)
+static mut DISK_QUEUE: VecDeque<DiskRequest> = VecDeque::new();        // 디스크 I/O 요청 큐
+static mut DISK_BUSY: Option<(DiskRequest, u32)> = None;        // 처리 중인 요청과 끝나는 cycle
+static mut DISK_HEAD: u32 = 0;      // 디스크 헤드의 실린더 위치
+static mut DISK_UP: bool = true;        // 헤드 이동 방향 (scan / cscan / look에서 사용, true면 실린더 번호가 커지는 방향)
+static mut DISK_WAIT: BTreeMap<u32, u32> = BTreeMap::new();     // pid -> 완료를 기다리는 읽기 요청 수
+static mut SWAP_SLOTS: BTreeSet<(u32, i32)> = BTreeSet::new();      // swap 공간에 기록된 페이지 (pid, page id) -> 다시 불러올 때 디스크 읽기 필요
+static mut DISK_STATS: (u32, u32, u32) = (0, 0, 0);     // (처리한 요청 수, 헤드 총 이동 거리, 요청 대기+처리 시간 합)
 static mut PQ: VecDeque<Page> = VecDeque::new();        // 물리 메모리에 들어오는 페이지 순서대로 저장하는 큐(FIFO, LRU일 때만 사용, LRU일 경우는 추가적으로 페이지가 사용될 때마다 큐 업데이트)
 static mut CYCLE_INFO: String = String::new();      // result 파일에 출력할 cycle 정보
 static mut CYCLE_DONE: bool = false;     // cycle이 끝나고 결과를 출력해야하면 true / 아직 출력할 때가 아니면 false
 static mut INPUT_DIR: String = String::new();       // 가상 프로그램들이 들어있는 폴더 경로 저장
 static mut CHANGE_ALGORITHM: String = String::new();        // 페이지 교체 알고리즘 저장
+static mut PT_LEVEL: u32 = 1;       // 페이지 테이블 단계 수 (1: 단일 페이지 테이블 / 2, 3: 다단계 페이지 테이블)
+static mut TRANSLATION: String = String::new();      // 주소 변환 구조 (없음: 프로세스별 페이지 테이블 / inverted: 역 페이지 테이블 / hashed: 해시 페이지 테이블)
+static mut HPT: Vec<Vec<[i32;3]>> = Vec::new();       // 해시 페이지 테이블: 버킷마다 (pid, page id, 물리메모리 index)를 요소로 가짐
+static mut PROBES: i32 = -1;        // 마지막 메모리 접근에서 주소 변환에 필요했던 탐색 횟수 (찾지 못했으면 -1)
+static mut PROBE_CYCLE: u32 = 0;        // 마지막 주소 변환이 일어난 cycle (그 cycle에만 탐색 횟수 출력)
+static mut PROBE_TOTAL: u32 = 0;        // 지금까지의 탐색 횟수 합
+static mut ACCESS_TOTAL: u32 = 0;       // 지금까지의 메모리 접근 횟수
+static mut FRAME_ALLOC: String = String::new();       // 프로세스별 프레임 할당 방식 (없음 / equal / proportional / priority)
+static mut REPLACEMENT: String = String::new();       // 페이지 교체 범위 (global: 전체 프레임에서 victim 선택 / local: 자신의 프레임에서만 victim 선택)
+static mut PRIORITY: BTreeMap<String, u32> = BTreeMap::new();       // 프로그램 이름별 우선순위 (지정되지 않으면 1)
+static mut WS_WINDOW: usize = 0;        // working set window 크기 (메모리 참조 횟수, 0이면 working set 추적 X)
+static mut THRASHING: bool = false;     // 현재 thrashing 상태인지
+static mut THRASH_EPISODES: Vec<(u32, u32)> = Vec::new();       // thrashing이 발생한 구간 (시작 cycle, 끝 cycle)
+static mut LOAD_EVENT: String = String::new();      // 이번 cycle에 load control이 한 일 (suspend / resume)
+static mut WS_STATS: BTreeMap<u32, (String, u32, u32, usize)> = BTreeMap::new();       // pid -> (이름, 메모리 참조 수, 페이지 폴트 수, 최대 working set 크기)
+static mut SWAP_LOW: usize = 0;     // 빈 프레임 수가 이보다 적으면 process 전체를 swap out (0이면 medium-term scheduler 사용 X)
+static mut SWAP_HIGH: usize = 0;        // 빈 프레임 수가 이 이상이면 suspend된 process를 swap in
+static mut SWAP_POLICY: String = String::new();       // swap out할 process 선택 방식 (blocked / largest / priority / youngest)
+static mut SWAP_EVENT: String = String::new();      // 이번 cycle에 medium-term scheduler가 한 일 (swap out / swap in)
+static mut KSWAPD_LOW: usize = 0;       // 빈 프레임 수가 이보다 적으면 kswapd가 깨어남 (0이면 kswapd 사용 X)
+static mut KSWAPD_HIGH: usize = 0;      // kswapd는 빈 프레임 수가 이만큼 될 때까지 페이지 회수
+static mut KSWAPD_EVENT: String = String::new();        // 이번 cycle에 kswapd가 한 일
+static mut KSWAPD_STATS: (u32, u32) = (0, 0);       // (kswapd가 깨어난 횟수, 회수한 페이지 수)
+static mut SWAP_SIZE: usize = 0;        // swap 공간 크기 (페이지 수, 0이면 무제한)
+static mut SWAP_PENDING: usize = 0;     // swap 공간에서 물리메모리로 들어오는 중인 페이지 수 (page fault 처리 중이면 1)
+static mut OOM_EVENT: String = String::new();       // 이번 cycle에 OOM killer가 종료시킨 process
+static mut OOM_KILLS: Vec<String> = Vec::new();     // OOM killer가 종료시킨 process 기록
+static mut PROT_EVENT: String = String::new();      // 이번 cycle에 protection fault로 종료된 process와 접근 내용
+const SIGNALS: [(u32, &str); 11] = [(1, "SIGHUP"), (2, "SIGINT"), (9, "SIGKILL"), (10, "SIGUSR1"), (11, "SIGSEGV"), (12, "SIGUSR2"), (14, "SIGALRM"), (15, "SIGTERM"), (17, "SIGCHLD"), (18, "SIGCONT"), (19, "SIGSTOP")];     // 지원하는 signal (번호, 이름)
+const SIGKILL: u32 = 9;
+const SIGSEGV: u32 = 11;
+const SIGALRM: u32 = 14;
+const SIGCHLD: u32 = 17;
+const SIGCONT: u32 = 18;
+const SIGSTOP: u32 = 19;
+const PERM_R: u32 = 4;      // 읽기 권한
+const PERM_W: u32 = 2;      // 쓰기 권한
+const PERM_X: u32 = 1;      // 실행 권한
+const SHARED_PAGE_BASE: i32 = 100;     // 공유 페이지 ID 시작 값 (process 페이지 ID와 겹치지 않도록) - 공유 메모리, 파일 페이지의 주인 pid는 0
+const DISK_CYLINDERS: u32 = 100;        // 디스크 실린더 수 (0 ~ 49: swap 영역, 50 ~ 99: 파일 영역)
+const SEEK_SPEED: u32 = 20;     // 1 cycle에 헤드가 이동하는 실린더 수
+const ROTATION: u32 = 1;        // 회전 지연 + 전송 시간 (cycle)
+const HPT_SIZE: usize = 8;      // 해시 페이지 테이블 버킷 수
+const PT_NODES: usize = 11;     // 테이블 노드 수 - 0: 최상위 디렉터리, 1~2: 중간 디렉터리(3단계일 때만), 3~10: 페이지 4개씩 담당하는 말단 테이블
 
 // 새로운 process 만들고 return하는 함수
-fn create_process(name: String, pid: u32, ppid: u32, status: String, lines: VecDeque<String>, virtual_mem: [Option<Page>;32], page_table: [[i32;2];32], page_id: i32, allocation_id: i32) -> Process {
+fn create_process(name: String, pid: u32, ppid: u32, lines: VecDeque<String>, virtual_mem: [Option<Page>;32], page_table: [[i32;2];32], page_id: i32, allocation_id: i32) -> Process {
+    let priority = unsafe {
+        match PRIORITY.get(&name) {
+            None => 1,
+            Some(&n) => n,
+        }
+    };
+    unsafe {
+        PROCS.insert(pid, ProcEntry {name: name.clone(), ppid, state: State::New, tgid: pid, orphan: false, fds: BTreeMap::new(), handlers: BTreeMap::new()});       // process table에 new 상태로 등록
+        SPACES.insert(pid, AddressSpace {virtual_mem, page_table, page_id, allocation_id, table_frame: [-1;PT_NODES], ws_refs: VecDeque::new()});
+    }
+    register_claims(pid, &lines);
     Process {
         name,
         pid,
+        tid: pid,
         ppid,     
-        status,
-        lines,
-        virtual_mem,
-        page_table,
-        page_id,
-        allocation_id,
+        lines: lines.clone(),
+        priority,
+        start_cycle: unsafe {CYCLE},
+        code: lines.iter().cloned().collect(),
+        pending: 0,
     }
 }
 
+// pid의 process(thread면 속한 process)의 주소 공간 반환
+fn space_of(pid: u32) -> &'static mut AddressSpace {
+    unsafe {SPACES.get_mut(&pid).unwrap()}
+}
+
+// pid의 process(thread면 속한 process)의 process table 항목 반환 - 파일 디스크립터와 signal handler는 process 단위
+fn entry_of(pid: u32) -> &'static mut ProcEntry {
+    unsafe {PROCS.get_mut(&pid).unwrap()}
+}
+
 // 새로운 page 만들고 return하는 함수
-fn create_page(pid: u32, page_id: i32, allocation_id: i32, authority: i32, count: u32) -> Page {
+fn create_page(pid: u32, page_id: i32, allocation_id: i32, perms: u32, cow: bool, count: u32, table: i32) -> Page {
     Page {
         pid,
         page_id,
         allocation_id,
-        authority,
+        perms,
+        cow,
         count,
+        table,
     }
 }
 
@@ -82,7 +271,7 @@ fn print_cycle()
             // 3. running 출력
             match &RUNNING {
                 None => temp.push_str("3. running: none\n"),
-                Some(p) => temp.push_str(&format!("3. running: {}({}, {})\n", p.pid, p.name, p.ppid))
+                Some(p) => temp.push_str(&format!("3. running: {}({}, {})\n", thread_label(p.pid, p.tid), p.name, p.ppid))
             }
             // 4. physical memory 출력
             temp.push_str("4. physical memory: \n");
@@ -98,11 +287,18 @@ fn print_cycle()
                         }
                     }
                     Some(p) => {
+                        // 테이블 페이지는 노드 이름으로 출력
+                        let mut label = if p.table == -1 {p.page_id.to_string()} else {pt_node_name(p.table as usize)};
+                        if page_pinned(p) {label.push('*');}        // 고정된 페이지는 * 표시
+                        let owner = match file_page(p) {        // 파일 페이지는 파일 이름, 공유 메모리 페이지는 shm으로 출력
+                            Some((file, _)) => file,
+                            None => if p.pid == 0 {"shm".to_string()} else {p.pid.to_string()},
+                        };
                         if i%4==3 {
-                            temp2.push_str(&format!("{}({})|", p.pid, p.page_id));
+                            temp2.push_str(&format!("{}({})|", owner, label));
                         }
                         else {
-                            temp2.push_str(&format!("{}({}) ", p.pid, p.page_id));
+                            temp2.push_str(&format!("{}({}) ", owner, label));
                         }
                     }
                 }
@@ -111,14 +307,14 @@ fn print_cycle()
             
             // 5. virtual memory, 6. page table 출력 (running process 존재 시)
             match &RUNNING {
-                None => temp.push_str("\n"),        // running process가 없을 경우 5, 6 출력 X
+                None => {},        // running process가 없을 경우 5, 6 출력 X
                 Some(p) => {
                     let mut temp2 = "|".to_string();
                     let mut temp3 = "|".to_string();
                     let mut temp4 = "|".to_string();
                     temp.push_str("5. virtual memory: \n");
                     for i in 0..32 {
-                        match p.virtual_mem[i] {       // running process의 가상 메모리 상황
+                        match space_of(p.pid).virtual_mem[i] {       // running process의 가상 메모리 상황
                             None => {
                                 if i%4==3 {
                                     temp2.push_str("-|");
@@ -132,25 +328,16 @@ fn print_cycle()
                             Some(q) => {
                                 if i%4==3 {
                                     temp2.push_str(&format!("{}|", q.page_id));
-                                    if q.authority == 0 {
-                                        temp4.push_str("W|");
-                                    }
-                                    else {
-                                        temp4.push_str("R|");
-                                    }
+                                    temp4.push_str(&format!("{}|", perm_string(q.perms, q.cow)));
                                 }
                                 else {
                                     temp2.push_str(&format!("{} ", q.page_id));
-                                    if q.authority == 0 {
-                                        temp4.push_str("W ");
-                                    }
-                                    else {
-                                        temp4.push_str("R ");
-                                    }
+                                    temp4.push_str(&format!("{} ", perm_string(q.perms, q.cow)));
                                 }
                             }
                         }
-                        if p.page_table[i][1] == -1 {
+                        let frame = frame_of(p, i);
+                        if frame == -1 {
                             if i%4==3 {
                                 temp3.push_str("-|");
                             }
@@ -160,17 +347,283 @@ fn print_cycle()
                         }
                         else {
                             if i%4==3 {
-                                temp3.push_str(&format!("{}|", p.page_table[i][1]));
+                                temp3.push_str(&format!("{}|", frame));
                             }
                             else {
-                                temp3.push_str(&format!("{} ", p.page_table[i][1]));
+                                temp3.push_str(&format!("{} ", frame));
                             }
                         }
                     }
                     temp.push_str(&format!("{}\n6. page table: \n", temp2));
-                    temp.push_str(&format!("{}\n{}\n\n", temp3, temp4));
+                    temp.push_str(&format!("{}\n{}\n", temp3, temp4));
+                }
+            }
+            // 7~. 옵션에 따라 추가되는 항목은 번호를 이어서 출력
+            let mut section = 7;
+            if let Some(p) = &RUNNING {
+                // 다단계 페이지 테이블일 경우 테이블 계층 구조 출력
+                if PT_LEVEL > 1 {
+                    temp.push_str(&format!("{section}. {}", pt_hierarchy(p)));
+                    section += 1;
+                }
+                // 역/해시 페이지 테이블일 경우 이번 메모리 접근의 탐색 횟수 출력
+                if !TRANSLATION.is_empty() && PROBES != -1 && PROBE_CYCLE == CYCLE {
+                    temp.push_str(&format!("{section}. translation: {} (probes: {}, average: {:.2})\n", TRANSLATION, PROBES, PROBE_TOTAL as f64 / ACCESS_TOTAL as f64));
+                    section += 1;
+                }
+            }
+            // 프레임 할당량을 사용할 경우 프로세스별 resident set 크기 / 할당량 출력
+            if !FRAME_ALLOC.is_empty() {
+                temp.push_str(&format!("{section}. resident set ({}, {}): ", FRAME_ALLOC, REPLACEMENT));
+                let mut sets: Vec<String> = Vec::new();
+                for (pid, quota) in frame_quotas() {
+                    sets.push(format!("{}: {}/{}", pid, resident_pages(pid), quota));
+                }
+                if sets.is_empty() {sets.push("none".to_string());}
+                temp.push_str(&format!("{}\n", sets.join(", ")));
+                section += 1;
+            }
+            // working set 추적 시 프로세스별 working set 크기, 페이지 폴트 빈도, load control 결과 출력
+            if WS_WINDOW > 0 {
+                temp.push_str(&format!("{section}. working set (window {}): ", WS_WINDOW));
+                let mut sets: Vec<String> = Vec::new();
+                for p in all_processes() {
+                    sets.push(format!("{}: {} (pff {:.2})", p.pid, working_set(p), fault_frequency(p)));
+                }
+                if sets.is_empty() {sets.push("none".to_string());}
+                temp.push_str(&format!("{}, total: {}/16", sets.join(", "), working_set_total()));
+                if THRASHING {temp.push_str(" [thrashing]");}
+                if !LOAD_EVENT.is_empty() {
+                    temp.push_str(&format!(", load control: {}", LOAD_EVENT));
+                    LOAD_EVENT = String::new();
+                }
+                temp.push_str("\n");
+                section += 1;
+            }
+            // medium-term scheduler 사용 시 빈 프레임 수와 이번 cycle의 swap 결과 출력
+            if SWAP_LOW > 0 {
+                temp.push_str(&format!("{section}. swap: free frames {} (low {}, high {})", free_frames(), SWAP_LOW, SWAP_HIGH));
+                if !SWAP_EVENT.is_empty() {
+                    temp.push_str(&format!(", {}", SWAP_EVENT));
+                    SWAP_EVENT = String::new();
+                }
+                temp.push_str("\n");
+                section += 1;
+            }
+            // kswapd가 동작한 cycle이면 회수 결과 출력
+            if !KSWAPD_EVENT.is_empty() {
+                temp.push_str(&format!("{section}. kswapd: {}\n", KSWAPD_EVENT));
+                KSWAPD_EVENT = String::new();
+                section += 1;
+            }
+            // swap 공간 크기가 정해져 있으면 사용량 출력
+            if SWAP_SIZE > 0 {
+                temp.push_str(&format!("{section}. backing store: {}/{} pages\n", swapped_pages(0), SWAP_SIZE));
+                section += 1;
+            }
+            // OOM killer가 동작한 cycle이면 종료된 process 출력
+            if !OOM_EVENT.is_empty() {
+                temp.push_str(&format!("{section}. oom-kill: {}\n", OOM_EVENT));
+                OOM_EVENT = String::new();
+                section += 1;
+            }
+            // protection fault가 발생한 cycle이면 위반한 접근 출력
+            if !PROT_EVENT.is_empty() {
+                temp.push_str(&format!("{section}. protection fault: {}, terminated\n", PROT_EVENT));
+                PROT_EVENT = String::new();
+                section += 1;
+            }
+            // page cache hit / miss / write-back이 있었던 cycle이면 내용 출력
+            if !FILE_EVENT.is_empty() {
+                temp.push_str(&format!("{section}. page cache: {}\n", FILE_EVENT));
+                FILE_EVENT = String::new();
+                section += 1;
+            }
+            // 파일시스템 명령어 결과 출력
+            if !FS_EVENT.is_empty() {
+                temp.push_str(&format!("{section}. fs: {}\n", FS_EVENT));
+                FS_EVENT = String::new();
+                section += 1;
+            }
+            // running process가 연 파일이 있으면 파일 디스크립터 목록 출력
+            if let Some(p) = &RUNNING {
+                if !entry_of(p.pid).fds.is_empty() {
+                    let fds: Vec<String> = entry_of(p.pid).fds.iter().map(|(fd, i)| {
+                        let (ino, path, offset, _) = &OPEN_FILES[i];
+                        format!("{} {} (offset {}/{})", fd, path, offset, INODES[ino].size)
+                    }).collect();
+                    temp.push_str(&format!("{section}. open files: {}\n", fds.join(", ")));
+                    section += 1;
+                }
+            }
+            // 디스크 모델 사용 시 헤드 위치, 처리 중인 요청, 요청 큐 출력
+            if !DISK_SCHED.is_empty() {
+                temp.push_str(&format!("{section}. disk ({}): head {}", DISK_SCHED, DISK_HEAD));
+                if let Some((req, finish)) = &DISK_BUSY {
+                    temp.push_str(&format!(", serving {} until #{}", disk_request_name(req), finish));
+                }
+                let queue: Vec<String> = DISK_QUEUE.iter().map(|req| disk_request_name(req)).collect();
+                if !queue.is_empty() {
+                    temp.push_str(&format!(", queue: {}", queue.join(" ")));
+                }
+                temp.push_str("\n");
+                section += 1;
+            }
+            // interrupt cycle이면 처리한 장치와 깨운 process 출력
+            if !INTERRUPT_EVENT.is_empty() {
+                temp.push_str(&format!("{section}. interrupt: {}\n", INTERRUPT_EVENT));
+                INTERRUPT_EVENT = String::new();
+                section += 1;
+            }
+            // I/O를 기다리는 process가 있으면 장치별 wait queue 출력 (맨 앞 process는 I/O가 끝나는 cycle과 함께)
+            if !IO_QUEUES.is_empty() {
+                let devices: Vec<String> = IO_QUEUES.iter().map(|(device, (queue, finish))| {
+                    let pids: Vec<String> = queue.iter().enumerate().map(|(i, e)| if i == 0 {format!("{}(until #{})", e.0.pid, finish)} else {e.0.pid.to_string()}).collect();
+                    format!("{} [{}]", device, pids.join(" "))
+                }).collect();
+                temp.push_str(&format!("{section}. devices: {}\n", devices.join(", ")));
+                section += 1;
+            }
+            // timer가 만료되었거나 timer 명령어를 처리한 cycle이면 내용 출력
+            if !TIMER_EVENT.is_empty() {
+                temp.push_str(&format!("{section}. timer: {}\n", TIMER_EVENT));
+                TIMER_EVENT = String::new();
+                section += 1;
+            }
+            // 등록된 timer가 있으면 만료 cycle 순서대로 출력
+            if !TIMERS.is_empty() {
+                let timers: Vec<String> = TIMERS.iter().map(|(at, pid, alarm)| format!("{} {} #{}", pid, if *alarm {"alarm at"} else {"sleep until"}, at)).collect();
+                temp.push_str(&format!("{section}. timers: {}\n", timers.join(", ")));
+                section += 1;
+            }
+            // signal을 보내거나 전달한 cycle이면 내용 출력
+            if !SIGNAL_EVENT.is_empty() {
+                temp.push_str(&format!("{section}. signal: {}\n", SIGNAL_EVENT));
+                SIGNAL_EVENT = String::new();
+                section += 1;
+            }
+            // 전달되지 않은 signal이 있는 process 출력
+            let pending: Vec<String> = all_processes().into_iter().chain(SQ.iter()).filter(|p| p.pending != 0).map(|p| {
+                let names: Vec<&str> = SIGNALS.iter().filter(|(n, _)| p.pending & (1 << n) != 0).map(|(_, name)| *name).collect();
+                format!("{} [{}]", p.pid, names.join(" "))
+            }).collect();
+            if !pending.is_empty() {
+                temp.push_str(&format!("{section}. pending signals: {}\n", pending.join(", ")));
+                section += 1;
+            }
+            // 공유 메모리 segment가 있으면 페이지와 attach한 process 수 출력
+            if !SHM.is_empty() {
+                let segments: Vec<String> = SHM.iter().map(|(key, (ids, refs))| {
+                    let ids: Vec<String> = ids.iter().map(|id| id.to_string()).collect();
+                    format!("key {} [{}] refs {}", key, ids.join(" "), refs)
+                }).collect();
+                temp.push_str(&format!("{section}. shared memory: {}\n", segments.join(", ")));
+                section += 1;
+            }
+            // suspend된 process가 있으면 상태와 함께 출력
+            if !SQ.is_empty() {
+                let suspended: Vec<String> = SQ.iter().map(|p| format!("{}({}, {})", thread_label(p.pid, p.tid), p.name, state_label(state_of(p.tid)))).collect();
+                temp.push_str(&format!("{section}. suspended: {}\n", suspended.join(" ")));
+                section += 1;
+            }
+            // orphan이 init에 입양된 cycle이면 입양된 process 출력
+            if !REPARENT_EVENT.is_empty() {
+                temp.push_str(&format!("{section}. reparent: {}\n", REPARENT_EVENT));
+                REPARENT_EVENT = String::new();
+                section += 1;
+            }
+            // thread를 만들거나 join한 cycle이면 결과 출력
+            if !THREAD_EVENT.is_empty() {
+                temp.push_str(&format!("{section}. thread: {}\n", THREAD_EVENT));
+                THREAD_EVENT = String::new();
+                section += 1;
+            }
+            // 동기화 객체를 넘겨주거나 실패한 cycle이면 결과 출력
+            if !SYNC_EVENT.is_empty() {
+                temp.push_str(&format!("{section}. sync: {}\n", SYNC_EVENT));
+                SYNC_EVENT = String::new();
+                section += 1;
+            }
+            // 동기화 객체가 있으면 상태와 각 객체의 wait queue 출력
+            if !MUTEXES.is_empty() || !SEMAPHORES.is_empty() || !CONDS.is_empty() || !LOCKS.is_empty() {
+                let waiting = |q: Vec<u32>| q.into_iter().map(tid_label).collect::<Vec<String>>().join(" ");
+                let mut objects: Vec<String> = MUTEXES.iter().map(|(id, m)| match m.0 {
+                    None => format!("mutex {} free [{}]", id, waiting(Vec::from(m.1.clone()))),
+                    Some(owner) => format!("mutex {} locked by {} [{}]", id, tid_label(owner), waiting(Vec::from(m.1.clone()))),
+                }).collect();
+                objects.extend(SEMAPHORES.iter().map(|(id, sem)| format!("sem {} = {} [{}]", id, sem.0, waiting(Vec::from(sem.1.clone())))));
+                objects.extend(CONDS.iter().map(|(id, q)| format!("cond {} [{}]", id, waiting(q.iter().map(|w| w.0).collect()))));
+                objects.extend(LOCKS.iter().map(|(id, l)| match l.0 {
+                    None => format!("lock {} free [{}]", id, waiting(Vec::from(l.1.clone()))),
+                    Some(owner) => format!("lock {} held by {} [{}]", id, tid_label(owner), waiting(Vec::from(l.1.clone()))),
+                }));
+                temp.push_str(&format!("{section}. sync objects: {}\n", objects.join(", ")));
+                section += 1;
+            }
+            // 우선순위 스케줄링이나 lock 프로토콜을 쓰면 각 thread의 실제 우선순위 출력 (기본 우선순위와 다르면 함께)
+            if SCHED == "priority" || !LOCK_PROTOCOL.is_empty() {
+                let priorities: Vec<String> = PROCS.iter().filter(|(_, e)| !matches!(e.state, State::Zombie(_) | State::Terminated)).filter_map(|(&tid, e)| {
+                    let base = find_thread(tid)?.priority;
+                    let effective = effective_priority(tid);
+                    Some(if effective == base {format!("{} {}", thread_label(e.tgid, tid), effective)} else {format!("{} {} (base {})", thread_label(e.tgid, tid), effective, base)})
+                }).collect();
+                if !priorities.is_empty() {
+                    temp.push_str(&format!("{section}. priorities: {}\n", priorities.join(", ")));
+                    section += 1;
                 }
             }
+            // 자원을 요청 / 반환한 cycle이면 결과 출력
+            if !RESOURCE_EVENT.is_empty() {
+                temp.push_str(&format!("{section}. resource: {}\n", RESOURCE_EVENT));
+                RESOURCE_EVENT = String::new();
+                section += 1;
+            }
+            // 자원 종류가 있으면 남은 개수 / 전체 개수와 가진 thread, 기다리는 thread 출력
+            if !RESOURCES.is_empty() {
+                let resources: Vec<String> = RESOURCES.iter().map(|(res, (total, free))| {
+                    let held: Vec<String> = HELD.iter().filter_map(|(t, h)| h.get(res).map(|k| format!("{}({})", tid_label(*t), k))).collect();
+                    let waiting: Vec<String> = RES_WAIT.iter().filter(|w| &w.1 == res).map(|w| format!("{}({})", tid_label(w.0), w.2)).collect();
+                    format!("{} {}/{} held [{}] waiting [{}]", res, free, total, held.join(" "), waiting.join(" "))
+                }).collect();
+                temp.push_str(&format!("{section}. resources: {}\n", resources.join(", ")));
+                section += 1;
+            }
+            // 자원 할당 그래프에 deadlock이 있으면 deadlock에 빠진 thread들의 wait-for 관계 출력
+            if !DEADLOCKED.is_empty() {
+                temp.push_str(&format!("{section}. deadlock: {}\n", DEADLOCKED.join(", ")));
+                section += 1;
+            }
+            else if !RECOVERED.is_empty() {     // 이번 cycle에 회복된 deadlock도 회복 전의 wait-for 관계 출력
+                temp.push_str(&format!("{section}. deadlock: {}\n", RECOVERED));
+                section += 1;
+            }
+            RECOVERED = String::new();
+            // deadlock을 회복한 cycle이면 한 일 출력
+            if !DEADLOCK_EVENT.is_empty() {
+                temp.push_str(&format!("{section}. deadlock recovery: {}\n", DEADLOCK_EVENT));
+                DEADLOCK_EVENT = String::new();
+                section += 1;
+            }
+            // zombie를 회수한 cycle이면 회수한 process와 exit status 출력
+            if !REAP_EVENT.is_empty() {
+                temp.push_str(&format!("{section}. reaped: {}\n", REAP_EVENT));
+                REAP_EVENT = String::new();
+                section += 1;
+            }
+            // process table의 모든 process를 상태와 함께 출력
+            let procs: Vec<String> = PROCS.iter().map(|(tid, e)| format!("{}({}, {})", thread_label(e.tgid, *tid), e.name, state_label(e.state))).collect();
+            temp.push_str(&format!("{section}. processes: {}\n", procs.join(" ")));
+            section += 1;
+            // pstree 옵션 사용 시 process tree와 ready / waiting queue 내용을 순서대로 출력
+            if PSTREE {
+                temp.push_str(&format!("{section}. pstree:\n{}", pstree()));
+                section += 1;
+                let ready: Vec<String> = RQ.iter().map(|p| thread_label(p.pid, p.tid)).collect();
+                let waiting: Vec<String> = WQ.iter().map(|p| format!("{}({})", thread_label(p.pid, p.tid), state_label(state_of(p.tid)))).collect();
+                temp.push_str(&format!("{section}. queues: ready [{}], waiting [{}]\n", ready.join(" "), waiting.join(" ")));
+            }
+            PROCS.retain(|_, e| e.state != State::Terminated);      // 출력이 끝난 terminated process는 process table에서 제거
+            temp.push_str("\n");
         }
         CYCLE_INFO.push_str(&temp);
     }
@@ -181,18 +634,35 @@ fn idle_or_schedule()
 {
     unsafe{
         MODE = String::from("kernel");
+        if RUNNING.is_none() {
+            kswapd();       // 빈 프레임이 low watermark보다 적으면 스케줄 전에 페이지 회수 cycle 진행
+            handle_interrupts();        // 끝난 장치 I/O가 있으면 스케줄 전에 interrupt cycle 진행
+        }
         CYCLE += 1;     // 1 cycle 소비
+        disk_progress();        // 이번 cycle까지 끝난 디스크 I/O 처리
+        timer_tick();       // 이번 cycle까지 만료된 timer 처리
+        detect_deadlock();      // 자원 할당 그래프의 deadlock 검사
         if !RUNNING.is_none() {return;}     // 이미 running 상태의 process가 있다면 스케줄 필요X
         else {
-            match RQ.pop_front() {
+            load_control();     // working set 추적 시 thrashing 확인 후 process suspend / resume
+            medium_term_schedule();     // 빈 프레임이 부족하면 process swap out / 여유가 생기면 swap in
+            deadlock_recovery();        // deadlock이 있으면 설정한 방법으로 회복
+            match next_ready() {
                 None => {
                     COMMAND = String::from("idle");     // ready queue is empty
+                    let next = next_event();
+                    if next > CYCLE + 1 {timer_event(format!("idle until #{}", next));}
                     CYCLE_DONE = true;
                     print_cycle();
+                    if next > CYCLE + 1 {CYCLE = next - 1;}     // timer를 기다리는 동안 할 일이 없으면 다음 timer 만료 직전으로 건너뜀
+                    if WQ.iter().any(|p| state_of(p.tid) == State::Blocked(Wait::Disk)) || !IO_QUEUES.is_empty() || !TIMERS.is_empty() {     // 디스크 / 장치 I/O, timer를 기다리는 process가 있으면 끝날 때까지 idle
+                        idle_or_schedule();
+                    }
                     return;
                 }
                 Some(p) => {
                     COMMAND = String::from("schedule");
+                    set_state(p.tid, State::Running);
                     RUNNING = Some(p);       // ready queue의 첫번째 process를 running으로
                     CYCLE_DONE = true;
                     print_cycle();
@@ -212,10 +682,10 @@ fn find_virtual_space(arg1: u32) -> u32 {
             None => result,
             Some(r) => {
                 for i in 0..(33-arg1) {
-                    if r.virtual_mem[i as usize].is_none() {
+                    if space_of(r.pid).virtual_mem[i as usize].is_none() {
                         let mut check: bool = true;
                         for j in 0..arg1 {
-                            if !r.virtual_mem[(i+j) as usize].is_none() {
+                            if !space_of(r.pid).virtual_mem[(i+j) as usize].is_none() {
                                 check = false;
                                 break;
                             }
@@ -234,10 +704,13 @@ fn find_virtual_space(arg1: u32) -> u32 {
     }
 }
 
-// 물리 메모리에서 arg1개 만큼의 빈 공간을 찾아 index를 배열에 넣어 반환하는 함수 
-fn find_physical_space(arg1: u32) -> Vec<u32> {
+// 물리 메모리에서 arg1개 만큼의 빈 공간을 찾아 index를 배열에 넣어 반환하는 함수 (running process가 OOM killer로 종료되면 None)
+fn find_physical_space(arg1: u32) -> Option<Vec<u32>> {
     let mut result:Vec<u32> = Vec::new();
     unsafe {
+        if REPLACEMENT.contains("local") {
+            return find_physical_space_local(arg1);
+        }
         // 물리 메모리에서 비어있는 공간 위치 저장하기
         for i in 0..16 {
             if PHYSICAL_MEM[i].is_none() {
@@ -249,27 +722,241 @@ fn find_physical_space(arg1: u32) -> Vec<u32> {
 
         // 물리메모리에서 페이지 교체가 필요할 경우
         while result.len() < arg1 as usize {
-            let victim:u32 = find_victim();     // 교체될 페이지
+            match reclaim_frame(&Vec::new(), &result) {     // 교체될 페이지
+                None => return None,     // running process가 OOM killer로 종료됨
+                Some(victim) => result.push(victim),
+            }
+        }
+    }
+    result.sort();
+    Some(result)      // pop을 하면 상위 index부터 튀어나오므로, v.get(0)부터 접근할 것
+}
+
+// 지역 교체일 경우 물리 메모리에서 arg1개 만큼의 공간을 찾아 index를 배열에 넣어 반환하는 함수
+// running process가 할당량보다 적게 쓰고 있으면 빈 공간(없으면 할당량을 넘긴 프로세스의 페이지)을, 할당량을 다 썼으면 자신의 페이지를 교체
+fn find_physical_space_local(arg1: u32) -> Option<Vec<u32>> {
+    let mut result:Vec<u32> = Vec::new();
+    unsafe {
+        let pid = match &RUNNING {
+            None => 0,
+            Some(r) => r.pid,
+        };
+        let quotas = frame_quotas();
+        let quota = match quotas.iter().find(|q| q.0 == pid) {
+            None => 16,
+            Some(q) => q.1,
+        };
+        while result.len() < arg1 as usize {
+            let free = (0..16u32).find(|i| PHYSICAL_MEM[*i as usize].is_none() && !result.contains(i));
+            let mut victim: u32 = 16;
+            if (resident_pages(pid) as usize + result.len()) < quota as usize {     // 할당량에 여유가 있는 경우
+                if let Some(i) = free {
+                    result.push(i);
+                    continue;
+                }
+                let over: Vec<u32> = quotas.iter().filter(|q| resident_pages(q.0) > q.1).map(|q| q.0).collect();
+                if !over.is_empty() && !swap_full() {victim = find_victim(&over);}
+            } else if !swap_full() {        // 할당량을 다 쓴 경우 자신의 페이지 중에서 교체
+                victim = find_victim(&vec![pid]);
+            }
+            if victim == 16 {       // 조건에 맞는 victim이 없으면 빈 공간 또는 전체 프레임에서 선택
+                victim = match free {
+                    Some(i) => i,
+                    None => match reclaim_frame(&Vec::new(), &result) {
+                        None => return None,     // running process가 OOM killer로 종료됨
+                        Some(i) => i,
+                    },
+                };
+            }
             result.push(victim);
         }
     }
     result.sort();
-    result      // pop을 하면 상위 index부터 튀어나오므로, v.get(0)부터 접근할 것
+    Some(result)
+}
+
+// victim 페이지를 내보내 빈 프레임 하나를 만들고 index 반환 (taken에 있는 index는 제외)
+// swap 공간이 부족하거나 내보낼 페이지가 없으면 OOM killer로 process를 종료시킨 뒤 다시 시도, running process 자신이 종료되면 None 반환
+fn reclaim_frame(candidates: &Vec<u32>, taken: &Vec<u32>) -> Option<u32> {
+    unsafe {
+        loop {
+            // 빈 프레임이 있으면 사용 (OOM kill로 비워진 프레임 포함)
+            if let Some(i) = (0..16u32).find(|i| PHYSICAL_MEM[*i as usize].is_none() && !taken.contains(i)) {
+                return Some(i);
+            }
+            if !swap_full() {
+                let victim = find_victim(candidates);
+                if victim != 16 {return Some(victim);}
+                if !candidates.is_empty() {
+                    let victim = find_victim(&Vec::new());
+                    if victim != 16 {return Some(victim);}
+                }
+            }
+            if oom_kill() {return None;}
+        }
+    }
+}
+
+// 가상 메모리에는 있지만 물리메모리에 없는(swap 공간에 있는) 페이지 수 (파일 페이지 제외) -> pid가 0이면 전체, 아니면 해당 pid가 주인인 페이지만
+fn swapped_pages(pid: u32) -> usize {
+    unsafe {
+        let mut n = 0;
+        for &(page_pid, page_id) in RMAP.keys() {
+            if pid != 0 && page_pid != pid {continue;}
+            if page_pid == 0 && FILE_PAGES.contains_key(&page_id) {continue;}      // 파일 페이지는 swap이 아닌 파일에 있음
+            let resident = PHYSICAL_MEM.iter().any(|p| match p {
+                None => false,
+                Some(q) => q.table == -1 && q.pid == page_pid && q.page_id == page_id,
+            });
+            if !resident {n += 1;}
+        }
+        n
+    }
+}
+
+// swap 공간이 가득 차서 더 이상 페이지를 내보낼 수 없는지 (물리메모리로 들어오는 중인 페이지는 제외)
+fn swap_full() -> bool {
+    unsafe {
+        SWAP_SIZE > 0 && swapped_pages(0) - SWAP_PENDING.min(swapped_pages(0)) >= SWAP_SIZE
+    }
+}
+
+// OOM killer: badness가 가장 큰 process를 exit 경로로 종료 -> running process 자신이 종료되었으면 true 반환
+// badness는 사용하는 페이지(물리메모리 + swap)가 많을수록, 최근에 생성되었을수록 크고 우선순위로 나눔
+fn oom_kill() -> bool {
+    unsafe {
+        let mut procs: Vec<&Process> = all_processes();
+        procs.extend(SQ.iter());
+        procs.retain(|p| p.tid == p.pid);       // thread가 아닌 process 단위로 종료 (main thread가 종료되면 나머지 thread도 종료)
+        if procs.len() > 1 {procs.retain(|p| p.pid != 1);}     // init은 다른 process가 없을 때만 종료
+        let mut target: (u32, u32) = (0, 0);        // (pid, badness)
+        for p in procs {
+            let age = CYCLE - p.start_cycle;
+            let pages = resident_pages(p.pid) + swapped_pages(p.pid) as u32;
+            let badness = (pages * 10 + 20 - age.min(20)) / p.priority.max(1);
+            if target.0 == 0 || badness >= target.1 {
+                target = (p.pid, badness);
+            }
+        }
+        let name = match find_thread(target.0) {
+            None => return false,
+            Some(p) => p.name.clone(),
+        };
+        let event = format!("{}({}) badness {}", target.0, name, target.1);
+        OOM_KILLS.push(format!("cycle #{}: {}", CYCLE, event));
+        if !OOM_EVENT.is_empty() {OOM_EVENT.push_str(", ");}
+        OOM_EVENT.push_str(&event);
+        let running_pid = match &RUNNING {
+            None => 0,
+            Some(r) => r.tid,
+        };
+        if target.0 == running_pid {        // running process 자신을 종료: 이번 cycle 출력 후 다음 process 스케줄
+            terminate_running(128 + SIGKILL as i32, true);      // OOM killer는 SIGKILL로 종료시킨 것으로 취급
+            CYCLE_DONE = true;
+            print_cycle();
+            idle_or_schedule();
+            return true;
+        }
+        // 다른 process 종료: running process는 잠시 ready queue 맨 앞에 두고 exit 경로 실행
+        let victim = if let Some(i) = RQ.iter().position(|p| p.tid == target.0) {RQ.remove(i)}
+            else if let Some(i) = WQ.iter().position(|p| p.tid == target.0) {WQ.remove(i)}
+            else if let Some(i) = SQ.iter().position(|p| p.tid == target.0) {SQ.remove(i)}
+            else {io_remove(target.0)};
+        let saved = RUNNING.take();
+        if let Some(r) = saved {RQ.push_front(r);}
+        RUNNING = victim;
+        terminate_running(128 + SIGKILL as i32, true);      // OOM killer는 SIGKILL로 종료시킨 것으로 취급
+        if running_pid != 0 {
+            if RQ.front().map_or(false, |p| p.tid == running_pid) {RUNNING = RQ.pop_front();}
+            else {      // running thread가 종료된 process의 thread였으면 함께 종료됨
+                CYCLE_DONE = true;
+                print_cycle();
+                idle_or_schedule();
+                return true;
+            }
+        }
+        false
+    }
+}
+
+// 물리메모리에서 해당 pid가 주인인 페이지 수 (resident set 크기)
+fn resident_pages(pid: u32) -> u32 {
+    unsafe {
+        let mut n = 0;
+        for i in 0..16 {
+            if let Some(p) = PHYSICAL_MEM[i] {
+                if p.pid == pid {n += 1;}
+            }
+        }
+        n
+    }
+}
+
+// 존재하는 모든 프로세스(running, new, ready queue, waiting queue, 장치 wait queue)를 pid 순서로 반환 (thread가 여러 개인 process는 하나만)
+fn all_processes() -> Vec<&'static Process> {
+    unsafe {
+        let mut result: Vec<&'static Process> = Vec::new();
+        if let Some(r) = &RUNNING {result.push(r);}
+        if let Some(n) = &NEWP {result.push(n);}
+        for p in RQ.iter().chain(WQ.iter()).chain(IO_QUEUES.values().flat_map(|d| d.0.iter().map(|e| &e.0))) {
+            result.push(p);
+        }
+        result.sort_by_key(|p| (p.pid, p.tid != p.pid));
+        result.dedup_by_key(|p| p.pid);     // 같은 process의 thread는 주소 공간이 같으므로 하나만 (main thread 우선)
+        result
+    }
+}
+
+// 프레임 할당 방식에 따라 각 프로세스의 프레임 할당량 계산 -> (pid, 할당량)을 pid 순서로 반환
+fn frame_quotas() -> Vec<(u32, u32)> {
+    unsafe {
+        let procs = all_processes();
+        let mut weights: Vec<u32> = Vec::new();
+        for p in &procs {
+            let w = if FRAME_ALLOC.contains("proportional") {      // 가상 메모리 크기에 비례
+                space_of(p.pid).virtual_mem.iter().filter(|v| !v.is_none()).count().max(1) as u32
+            } else if FRAME_ALLOC.contains("priority") {        // 우선순위에 비례
+                p.priority.max(1)
+            } else {        // 균등 분배
+                1
+            };
+            weights.push(w);
+        }
+        let total: u32 = weights.iter().sum();
+        let mut quotas: Vec<u32> = weights.iter().map(|w| 16 * w / total.max(1)).collect();
+        // 나머지 프레임은 나머지가 큰 프로세스부터 하나씩 분배
+        let mut order: Vec<usize> = (0..procs.len()).collect();
+        order.sort_by_key(|&i| std::cmp::Reverse(16 * weights[i] % total.max(1)));
+        let mut left = 16 - quotas.iter().sum::<u32>().min(16);
+        for i in order {
+            if left == 0 {break;}
+            quotas[i] += 1;
+            left -= 1;
+        }
+        procs.iter().zip(quotas).map(|(p, q)| (p.pid, q.max(1))).collect()
+    }
 }
 
 // 페이지 교체 알고리즘에 맞게 victim 페이지 찾고 물리 메모리에서 해제 & 페이지 테이블 업데이트 & 비워진 공간의 index 반환
-fn find_victim() -> u32{
+// candidates가 비어있지 않으면 해당 pid들이 주인인 페이지 중에서만 victim 선택 (없으면 16 반환)
+// 고정(pinned)된 페이지는 어떤 알고리즘에서도 선택하지 않음 -> 모든 프레임이 고정되어 있으면 16 반환 (reclaim_frame에서 OOM killer로 처리)
+fn find_victim(candidates: &Vec<u32>) -> u32{
     unsafe {
         let mut victim: Option<Page> = None;
         let mut index: u32 = 16;
         if CHANGE_ALGORITHM.contains("fifo") || CHANGE_ALGORITHM.contains("lru") {
-            victim = PQ.pop_front();
+            match PQ.iter().position(|p| (candidates.is_empty() || candidates.contains(&p.pid)) && !page_pinned(*p)) {
+                None => {},
+                Some(i) => victim = PQ.remove(i),
+            }
         }
         else if CHANGE_ALGORITHM.contains("lfu") {
             let mut min = -1; 
             for i in 0..16 {
                 if !PHYSICAL_MEM[i].is_none() {
                     let page = PHYSICAL_MEM[i].unwrap();
+                    if !candidates.is_empty() && !candidates.contains(&page.pid) {continue;}
+                    if page_pinned(page) {continue;}        // 고정된 페이지는 victim 후보에서 제외
                     if min == -1 || min > page.count as i32{
                         min = page.count as i32;
                         victim = PHYSICAL_MEM[i];
@@ -283,6 +970,8 @@ fn find_victim() -> u32{
             for i in 0..16 {
                 if !PHYSICAL_MEM[i].is_none() {
                     let page = PHYSICAL_MEM[i].unwrap();
+                    if !candidates.is_empty() && !candidates.contains(&page.pid) {continue;}
+                    if page_pinned(page) {continue;}        // 고정된 페이지는 victim 후보에서 제외
                     if max < page.count as i32{
                         max = page.count as i32;
                         victim = PHYSICAL_MEM[i];
@@ -291,66 +980,54 @@ fn find_victim() -> u32{
                 }
             }
         }
+        if victim.is_none() {return 16;}        // 조건에 맞는 victim 없음
         // fifo 또는 lru일 경우
         if CHANGE_ALGORITHM.contains("fifo") || CHANGE_ALGORITHM.contains("lru") {
             // 1. victim page가 위치한 물리메모리에서의 index 구하기
             for i in 0..16 {
                 if !PHYSICAL_MEM[i].is_none() {
                     let p = PHYSICAL_MEM[i].unwrap();
-                    if p.pid == victim.unwrap().pid && p.page_id == victim.unwrap().page_id {
+                    if p.pid == victim.unwrap().pid && p.page_id == victim.unwrap().page_id && p.table == victim.unwrap().table {
                         index = i as u32;
                         break;
                     }
                 }
             }
         }
-        // victim page를 가지는 모든 프로세스들에게서 페이지 테이블 업데이트
-            // 1. running process 탐색
-        match &RUNNING {
-            None => return index,
-            Some(r) => {
-                for i in 0..32 {
-                    if r.virtual_mem[i].is_none() {continue;}
-                    let target = r.virtual_mem[i].unwrap();
-                    if target.pid == victim.unwrap().pid && target.page_id == victim.unwrap().page_id {
-                        let mut c:Process = r.clone();
-                        c.page_table[i][1] = -1;
-                        RUNNING = Some(c);
-                        break;
-                    }
-                }
-            }
+        evict_frame(index);
+        index
+    }
+}
+
+// 물리메모리 index의 페이지를 내보내고 해당 페이지를 가리키던 페이지 테이블들 업데이트
+fn evict_frame(index: u32) {
+    unsafe {
+        let victim = match PHYSICAL_MEM[index as usize] {
+            None => return,
+            Some(p) => p,
+        };
+        if CHANGE_ALGORITHM.contains("fifo") || CHANGE_ALGORITHM.contains("lru") {
+            remove_PQ(victim);
         }
-            // 2. ready queue 탐색 
-        let size = RQ.len();
-        for _ in 0..size {
-            let mut p: Process = RQ.pop_front().unwrap();
-            for i in 0..32 {
-                if p.virtual_mem[i].is_none() {continue;}
-                let target = p.virtual_mem[i].unwrap();
-                if target.pid == victim.unwrap().pid && target.page_id == victim.unwrap().page_id {
-                    p.page_table[i][1] = -1;
-                    break;
-                }
-            }
-            RQ.push_back(p);
+        if file_page(victim).is_some() {        // 수정된 파일 페이지는 내보내기 전에 파일에 기록
+            write_back(victim.page_id);
         }
-            // 3. waiting queue 탐색
-        let size = WQ.len();
-        for _ in 0..size {
-            let mut p: Process = WQ.pop_front().unwrap();
-            for i in 0..32 {
-                if p.virtual_mem[i].is_none() {continue;}
-                let target = p.virtual_mem[i].unwrap();
-                if target.pid == victim.unwrap().pid && target.page_id == victim.unwrap().page_id {
-                    p.page_table[i][1] = -1;
-                    break;
-                }
+        else if victim.table == -1 && !DISK_SCHED.is_empty() {      // 익명 페이지는 swap 공간에 기록 (기다리는 process 없음)
+            SWAP_SLOTS.insert((victim.pid, victim.page_id));
+            disk_submit(0, false, victim.pid, victim.page_id);
+        }
+        if victim.table != -1 {     // victim이 테이블 페이지일 경우 주인 프로세스의 테이블 노드만 업데이트
+            pt_victim_update(victim);
+        }
+        else if !TRANSLATION.is_empty() {       // 역/해시 페이지 테이블일 경우 전역 테이블에서만 제거하면 되므로 프로세스 탐색 필요X
+            hpt_remove(victim.pid, victim.page_id);
+        }
+        else {      // 역매핑으로 victim page를 가지는 프로세스들만 페이지 테이블 업데이트
+            for (pid, i) in rmap_mappers(victim.pid, victim.page_id) {
+                if let Some(s) = SPACES.get_mut(&pid) {s.page_table[i][1] = -1;}
             }
-            WQ.push_back(p);
         }
         PHYSICAL_MEM[index as usize] = None;     // victim 자리 비우기
-        index
     }
 }
 
@@ -368,26 +1045,36 @@ fn memory_allocate(arg1: u32) {
         CYCLE += 1;
         COMMAND = String::from("system call");
         let virtual_index = find_virtual_space(arg1);
-        let physical_index: Vec<u32> = find_physical_space(arg1);
+        let physical_index: Vec<u32> = match find_physical_space(arg1) {
+            None => return,     // running process가 OOM killer로 종료됨
+            Some(v) => v,
+        };
         match &RUNNING {
             None => return,
             Some(r) => {
-                let mut c = r.clone();
-                c.allocation_id += 1;
+                let s = space_of(r.pid);
+                s.allocation_id += 1;
                 for i in 0..arg1 {
-                    c.page_id += 1;
-                    let new_page:Page = create_page(c.pid, c.page_id, c.allocation_id, 0, 1);     // 새로운 페이지 생성
+                    s.page_id += 1;
+                    let new_page:Page = create_page(r.pid, s.page_id, s.allocation_id, PERM_R | PERM_W, false, 1, -1);     // 새로운 페이지 생성
                     let index_v = i+virtual_index;
                     let &index_p: &u32 = physical_index.get(i as usize).unwrap();
-                    c.virtual_mem[index_v as usize] = Some(new_page);        // 가상메모리에 할당
+                    s.virtual_mem[index_v as usize] = Some(new_page);        // 가상메모리에 할당
                     PHYSICAL_MEM[index_p as usize] = Some(new_page);       // 물리메모리에 할당
                     if CHANGE_ALGORITHM.contains("fifo") || CHANGE_ALGORITHM.contains("lru") {
                         PQ.push_back(new_page);
                     }
-                    c.page_table[index_v as usize][0] = new_page.page_id;
-                    c.page_table[index_v as usize][1] = index_p as i32;       // 새롭게 할당된 페이지에 대해 페이지 테이블 업데이트
+                    s.page_table[index_v as usize][0] = new_page.page_id;
+                    s.page_table[index_v as usize][1] = index_p as i32;       // 새롭게 할당된 페이지에 대해 페이지 테이블 업데이트
+                    hpt_insert(new_page.pid, new_page.page_id, index_p);
+                    rmap_add(new_page, r.pid, index_v as usize);
                 }
-                RQ.push_back(c);        // running -> ready
+                if PT_LEVEL > 1 {       // 다단계 페이지 테이블일 경우 새로 할당된 페이지들을 담당하는 테이블 페이지 할당
+                    for i in 0..arg1 {
+                        if !pt_load((i+virtual_index) as usize) {return;}       // 도중에 OOM killer로 종료되면 중단
+                    }
+                }
+                make_ready(RUNNING.clone().unwrap());        // running -> ready
                 RUNNING = None;
             }
         }
@@ -400,50 +1087,105 @@ fn memory_allocate(arg1: u32) {
     }
 }
 
-// ready queue, waiting queue에서 특정 페이지 ID의 페이지를 갖는 모든 프로세스에 대해 페이지 권한 W로 변경,
+// ready queue, waiting queue에서 특정 페이지 ID의 페이지를 갖는 모든 프로세스에 대해 CoW 공유 해제,
 // 자식 프로세스의 경우 물리메모리를 가리키지 않게 하는 함수 
 fn rq_wq_search_and_w_change(page_pid: u32, page_id: u32) {
     unsafe {
-        // 1) ready queue에서 해당 페이지를 갖는 프로세스(부모 or 자식) 찾기 
-        let mut size = RQ.len();
-        for _ in 0..size {
-            let mut target = RQ.pop_front().unwrap();
-            for i in 0..32 {
-                if !target.virtual_mem[i].is_none() {
-                    let old_page = target.virtual_mem[i].unwrap();
-                    if old_page.pid == page_pid && old_page.page_id == page_id as i32 {
-                        let mut new_page = authority_change(old_page, 1);       // 해당 페이지 권한 W로 변경
-                        if target.pid != page_pid {     // 자식 프로세스일 경우
-                            new_page.pid = target.pid;      // 해당 페이지 부모로부터 독립
-                            target.page_table[i][1] = -1;       // 자식 프로세스는 물리메모리를 가리키지 않게 함
-                        }
-                        target.virtual_mem[i] = Some(new_page);
-                        break;
-                    }
-                }
+        let running_pid = match &RUNNING {
+            None => 0,
+            Some(r) => r.pid,
+        };
+        // 역매핑으로 해당 페이지를 갖는 프로세스(부모 or 자식)만 찾기
+        for (pid, i) in rmap_mappers(page_pid, page_id as i32) {
+            if pid == running_pid {continue;}       // running process(와 같은 주소 공간의 thread)는 호출한 쪽에서 처리
+            let s = match SPACES.get_mut(&pid) {
+                None => continue,
+                Some(s) => s,
+            };
+            let old_page = s.virtual_mem[i].unwrap();
+            let mut new_page = cow_change(old_page, false);       // 해당 페이지 CoW 공유 해제
+            if pid != page_pid {     // 자식 프로세스일 경우
+                new_page.pid = pid;      // 해당 페이지 부모로부터 독립
+                rmap_remove(old_page, pid, i);
+                rmap_add(new_page, pid, i);
+                s.page_table[i][1] = -1;        // 자식 프로세스는 물리메모리를 가리키지 않게 함
             }
-            RQ.push_back(target);
+            s.virtual_mem[i] = Some(new_page);
         }
-        // 2) waiting queue에서 해당 페이지를 갖는 프로세스(부모 or 자식) 찾기 
-        size = WQ.len();
-        for _ in 0..size {
-            let mut target = WQ.pop_front().unwrap();
-            for i in 0..32 {
-                if !target.virtual_mem[i].is_none() {
-                    let old_page = target.virtual_mem[i].unwrap();
-                    if old_page.pid == page_pid && old_page.page_id == page_id as i32 {
-                        let mut new_page = authority_change(old_page, 1);       // 해당 페이지 권한 W로 변경
-                        target.virtual_mem[i] = Some(new_page);
-                        if target.pid != page_pid {     // 자식 프로세스일 경우
-                            new_page.pid = target.pid;      // 해당 페이지 부모로부터 독립
-                            target.page_table[i][1] = -1;       // 자식 프로세스는 물리메모리를 가리키지 않게 함
-                        }
-                        target.virtual_mem[i] = Some(new_page);
-                        break;
-                    }
-                }
+    }
+}
+
+// tid에 해당하는 thread(process면 pid)를 running, new, ready queue, waiting queue, 장치 wait queue, suspended queue 순서로 찾아 반환 (queue 순서는 바뀌지 않음)
+fn find_thread(tid: u32) -> Option<&'static mut Process> {
+    unsafe {
+        if let Some(r) = RUNNING.as_mut() {
+            if r.tid == tid {return Some(r);}
+        }
+        if let Some(n) = NEWP.as_mut() {
+            if n.tid == tid {return Some(n);}
+        }
+        if let Some(p) = RQ.iter_mut().find(|p| p.tid == tid) {return Some(p);}
+        if let Some(p) = WQ.iter_mut().find(|p| p.tid == tid) {return Some(p);}
+        if let Some(p) = IO_QUEUES.values_mut().flat_map(|d| d.0.iter_mut()).find(|e| e.0.tid == tid) {return Some(&mut p.0);}
+        SQ.iter_mut().find(|p| p.tid == tid)
+    }
+}
+
+// pid의 process에 속한 모든 thread 반환 -> 주소 공간(가상 메모리, 페이지 테이블)을 바꿀 때 모든 thread에 똑같이 적용
+fn threads_of(pid: u32) -> Vec<&'static mut Process> {
+    unsafe {
+        let mut result: Vec<&'static mut Process> = Vec::new();
+        if let Some(r) = RUNNING.as_mut() {
+            if r.pid == pid {result.push(r);}
+        }
+        if let Some(n) = NEWP.as_mut() {
+            if n.pid == pid {result.push(n);}
+        }
+        for p in RQ.iter_mut().chain(WQ.iter_mut()).chain(IO_QUEUES.values_mut().flat_map(|d| d.0.iter_mut().map(|e| &mut e.0))).chain(SQ.iter_mut()) {
+            if p.pid == pid {result.push(p);}
+        }
+        result
+    }
+}
+
+// trace에 출력할 process 이름 - thread는 pid:tid
+fn thread_label(pid: u32, tid: u32) -> String {
+    if pid == tid {pid.to_string()} else {format!("{}:{}", pid, tid)}
+}
+
+// tid만 알 때 trace에 출력할 이름 (process table에서 속한 process를 찾음)
+fn tid_label(tid: u32) -> String {
+    unsafe {
+        thread_label(PROCS.get(&tid).map_or(tid, |e| e.tgid), tid)
+    }
+}
+
+// 역매핑에 페이지를 매핑하는 (프로세스 pid, 가상 메모리 index) 추가
+fn rmap_add(page: Page, pid: u32, v_index: usize) {
+    unsafe {
+        RMAP.entry((page.pid, page.page_id)).or_default().push((pid, v_index));
+    }
+}
+
+// 역매핑에서 페이지를 매핑하는 (프로세스 pid, 가상 메모리 index) 제거
+fn rmap_remove(page: Page, pid: u32, v_index: usize) {
+    unsafe {
+        let key = (page.pid, page.page_id);
+        if let Some(mappers) = RMAP.get_mut(&key) {
+            mappers.retain(|m| *m != (pid, v_index));
+            if mappers.is_empty() {
+                RMAP.remove(&key);
             }
-            WQ.push_back(target);
+        }
+    }
+}
+
+// 페이지 (pid, page id)를 매핑하는 모든 (프로세스 pid, 가상 메모리 index) 반환
+fn rmap_mappers(page_pid: u32, page_id: i32) -> Vec<(u32, usize)> {
+    unsafe {
+        match RMAP.get(&(page_pid, page_id)) {
+            None => Vec::new(),
+            Some(mappers) => mappers.clone(),
         }
     }
 }
@@ -454,7 +1196,7 @@ fn remove_PQ(victim: Page) {
         let size = PQ.len();
         for _ in 0..size {
             let p = PQ.pop_front().unwrap();
-            if p.pid == victim.pid && p.page_id == victim.page_id {continue;}       // victim 페이지를 찾았다면 pop
+            if p.pid == victim.pid && p.page_id == victim.page_id && p.table == victim.table {continue;}       // victim 페이지를 찾았다면 pop
             else {PQ.push_back(p);}     // victim이 아닌 페이지들은 다시 push
         }
     }
@@ -465,36 +1207,51 @@ fn release(allocation_id: u32) {
         match &RUNNING {
             None => return,
             Some(r) => {
-                let mut c = r.clone();
+                let c = r.clone();
+                let mut detached: Vec<u32> = Vec::new();        // detach되는 공유 메모리 segment key
                 for i in 0..32 {
-                    if c.virtual_mem[i].is_none() {continue;}
-                    let page = c.virtual_mem[i].unwrap();
+                    if space_of(c.pid).virtual_mem[i].is_none() {continue;}
+                    let page = space_of(c.pid).virtual_mem[i].unwrap();
                     if page.allocation_id == allocation_id as i32 { 
-                        let p_index = c.page_table[i][1];       // 물리메모리에서의 위치 
-                        c.virtual_mem[i] = None;        // 가상메모리 해제
-                        c.page_table[i][0] = -1;
-                        c.page_table[i][1] = -1;    // 페이지 테이블 업데이트
-                        if page.authority == 0 {        // 해당 페이지 권한이 W일 경우
+                        let p_index = frame_of(&c, i);       // 물리메모리에서의 위치 
+                        space_of(c.pid).virtual_mem[i] = None;        // 가상메모리 해제
+                        rmap_remove(page, c.pid, i);
+                        PINNED.remove(&(c.pid, i));     // 해제되는 매핑의 고정도 풀림
+                        space_of(c.pid).page_table[i][0] = -1;
+                        space_of(c.pid).page_table[i][1] = -1;    // 페이지 테이블 업데이트
+                        if page.pid == 0 {      // 공유 메모리 페이지는 매핑만 해제 (프레임은 segment가 없어질 때 해제)
+                            if let Some((&key, _)) = SHM.iter().find(|(_, seg)| seg.0.contains(&page.page_id)) {
+                                if !detached.contains(&key) {detached.push(key);}
+                            }
+                            else if p_index != -1 {     // 파일 페이지는 수정되었으면 write-back, 프레임은 page cache로 남음
+                                write_back(page.page_id);
+                            }
+                        } else if !page.cow {        // CoW 공유 중이 아닌 페이지일 경우
                             if p_index != -1 {
                                 if CHANGE_ALGORITHM.contains("fifo") || CHANGE_ALGORITHM.contains("lru") {
                                     remove_PQ(PHYSICAL_MEM[p_index as usize].unwrap());
                                 }
+                                hpt_remove(page.pid, page.page_id);
                                 PHYSICAL_MEM[p_index as usize] = None;  // 물리메모리에 존재 시 해제
                             }
-                        } else {        // 해당 페이지 권한이 R일 경우
-                            rq_wq_search_and_w_change(page.pid, page.page_id as u32);       // 해당 페이지를 가지는 모든 부모, 자식, 형제 프로세스에서 권한 W로 변경
+                        } else {        // CoW 공유 중인 페이지일 경우
+                            rq_wq_search_and_w_change(page.pid, page.page_id as u32);       // 해당 페이지를 가지는 모든 부모, 자식, 형제 프로세스에서 CoW 해제
                             if page.pid == c.pid {      // running process가 부모일 경우
                                 if p_index != -1 {
                                     if CHANGE_ALGORITHM.contains("fifo") || CHANGE_ALGORITHM.contains("lru") {
                                         remove_PQ(PHYSICAL_MEM[p_index as usize].unwrap());
                                     }
+                                    hpt_remove(page.pid, page.page_id);
                                     PHYSICAL_MEM[p_index as usize] = None;  // 물리메모리에 존재 시 해제
                                 }
                             }
                         }    
                     }
                 }
-                RUNNING = Some(c);
+                pt_free_unused(&c);     // 비게 된 테이블 페이지 해제
+                for key in detached {
+                    shm_put(key);
+                }
             }
         }
     }
@@ -517,7 +1274,7 @@ fn memory_release(arg1: u32) {
         match &RUNNING {
             None => return,
             Some(r) => {
-                RQ.push_back(r.clone());
+                make_ready(r.clone());
                 RUNNING = None;     // running process는 ready 상태가 됨
             }
         }
@@ -530,504 +1287,3494 @@ fn memory_release(arg1: u32) {
     }
 }
 
-// 인자로 페이지를 받고, 페이지의 권한만 W에서 R 또는 R에서 W로 변경해서 새 페이지를 반환하는 함수
-fn authority_change(old_page: Page, authority: u32) -> Page {
-    if authority == 0 {
-        return create_page(old_page.pid, old_page.page_id, old_page.allocation_id, 1, old_page.count);
-    }
-    return create_page(old_page.pid, old_page.page_id, old_page.allocation_id, 0, old_page.count);
-}
-
-// 인자로 물리메모리에서의 인덱스를 받고, 해당 위치 페이지의 참조 카운트 +1 하기
-fn p_mem_count_plus(p_index: u32) {
+// 페이지가 고정되어 있는지 확인 -> 일반 페이지는 매핑 중 하나라도 고정되어 있으면,
+// 테이블 페이지는 주인 프로세스의 고정된 매핑 경로에 있으면 고정됨
+fn page_pinned(page: Page) -> bool {
     unsafe {
-        let old_page = PHYSICAL_MEM[p_index as usize].unwrap();
-        let new_page = create_page(old_page.pid, old_page.page_id, old_page.allocation_id, old_page.authority, old_page.count+1);
-        PHYSICAL_MEM[p_index as usize] = Some(new_page);
+        if PINNED.is_empty() {return false;}
+        if page.table == -1 {
+            rmap_mappers(page.pid, page.page_id).iter().any(|m| PINNED.contains(m))
+        }
+        else {
+            PINNED.iter().any(|&(pid, i)| pid == page.pid && pt_path(i).contains(&(page.table as usize)))
+        }
     }
 }
 
-// 명령어 memory_read 처리
-fn memory_read(arg1: u32) {
+// 명령어 memory_lock 처리 -> allocation의 모든 페이지를 고정하고 물리메모리에 없는 페이지는 바로 불러옴
+fn memory_lock(arg1: u32) {
     unsafe {
-        // 1. 첫 번째 cycle: 읽기 시도
+        // 1. 첫 번째 cycle 출력
         CYCLE += 1;
-        COMMAND = String::from(format!("memory_read {arg1}"));
-        // 물리메모리에 해당 페이지가 존재하는지 알아보기
-        let mut p_index = -1;
-        let mut target = None;      // read하고자 하는 페이지 저장
-        match &RUNNING {
+        COMMAND = String::from(format!("memory_lock {arg1}"));
+        CYCLE_DONE = true;
+        print_cycle();
+        MODE = String::from("kernel");      // 모드 스위칭
+
+        // 2. 두 번째 cycle
+        CYCLE += 1;
+        COMMAND = String::from("system call");
+        let (pid, targets) = match &RUNNING {
             None => return,
             Some(r) => {
+                let mut targets: Vec<(usize, Page)> = Vec::new();
                 for i in 0..32 {
-                    if !r.virtual_mem[i].is_none() && r.virtual_mem[i].unwrap().page_id == arg1 as i32 {
-                        p_index = r.page_table[i][1];   // 물리메모리에서의 위치
-                        target = r.virtual_mem[i];      
-                        break;
+                    if let Some(page) = space_of(r.pid).virtual_mem[i] {
+                        if page.allocation_id == arg1 as i32 {targets.push((i, page));}
                     }
                 }
+                (r.pid, targets)
             }
+        };
+        for &(i, _) in &targets {
+            PINNED.insert((pid, i));        // 먼저 모두 고정해야 불러오는 도중 서로를 내보내지 않음
         }
-        if p_index != -1 {
-            p_mem_count_plus(p_index as u32);     // 참조 카운트 +1
-            if CHANGE_ALGORITHM.contains("lru") {      // 페이지 교체 알고리즘이 lru일 경우 PQ 업데이트
-                lru_update(PHYSICAL_MEM[p_index as usize].unwrap());
+        for (i, page) in targets {
+            if !pt_resident(i) && !pt_load(i) {return;}      // 경로의 테이블 페이지 불러오기 (도중에 OOM killer로 종료되면 중단)
+            if running_frame(i) == -1 {
+                match page_fault_handler(page.page_id as u32) {
+                    None => return,     // 불러오는 도중 OOM killer에 의해 종료됨
+                    Some(p_index) => page_table_frame_add(page, p_index),
+                }
             }
-            CYCLE_DONE = true;
-            print_cycle();
-            running_process();      // 다음 프로세스 명령어 실행
-            return;
         }
-        // 물리메모리에 존재하지 않을 경우
-        CYCLE_DONE = true;
-        print_cycle();
-        // 2. 두 번째 cycle: 페이지 폴트 핸들러
-        CYCLE += 1;
-        COMMAND = String::from("fault");
-        MODE = String::from("kernel");      // 모드 스위칭
-        p_index = page_fault_handler(arg1) as i32;
-        // 물리메모리에 새로 할당 후 페이지 테이블 업데이트
-        page_table_frame_add(target.unwrap(), p_index as u32);
         match &RUNNING {
             None => return,
-            Some(r) => {
-                RQ.push_back(r.clone());
-                RUNNING = None;     // running -> ready
-            }
+            Some(_) => ready_or_block(),     // running -> ready (디스크 읽기를 기다려야 하면 waiting)
         }
         CYCLE_DONE = true;
         print_cycle();
+
         // 3. 세 번째 cycle
         idle_or_schedule();     // scheduling
         return;
     }
 }
 
-// 해당 페이지를 갖는 페이지 테이블 업데이트 -> arg1이 page ID인 곳에 새로운 물리메모리 인덱스 (idx) 넣기
-fn page_table_frame_add(target: Page, idx: u32) {
-    unsafe {
-        // 1. running process 탐색 
-        match &RUNNING {
-            None => return,
-            Some(r) => {
-                let mut c = r.clone();
-                for i in 0..32 {
-                    if c.virtual_mem[i].is_none() {continue;}
-                    let page = c.virtual_mem[i].unwrap();
-                    if page.pid == target.pid && page.page_id == target.page_id {
-                        c.page_table[i][1] = idx as i32;
-                        break;
-                    }
-                }
-                RUNNING = Some(c);
-            }
-        }
-        // 2. ready queue 탐색
-        let mut size = RQ.len();
-        for _ in 0..size {
-            let mut process = RQ.pop_front().unwrap();
-            for i in 0..32 {
-                if process.virtual_mem[i].is_none() {continue;}
-                let page = process.virtual_mem[i].unwrap();
-                if page.pid == target.pid && page.page_id == target.page_id {
-                    process.page_table[i][1] = idx as i32;
-                    break;
-                }
-            }
-            RQ.push_back(process);
-        }
-        // 3. waiting queue 탐색
-        size = WQ.len();
-        for _ in 0..size {
-            let mut process = WQ.pop_front().unwrap();
-            for i in 0..32 {
-                if process.virtual_mem[i].is_none() {continue;}
-                let page = process.virtual_mem[i].unwrap();
-                if page.pid == target.pid && page.page_id == target.page_id {
-                    process.page_table[i][1] = idx as i32;
-                    break;
-                }
-            }
-            WQ.push_back(process);
-        }
-    }
-}
-
-// 명령어 memory_write 처리 함수
-fn memory_write(arg1: u32) {
+// 명령어 memory_unlock 처리 -> allocation의 모든 페이지 고정 해제 (페이지는 물리메모리에 그대로 남음)
+fn memory_unlock(arg1: u32) {
     unsafe {
-        // 1. 첫 번째 cycle : 유저 모드
+        // 1. 첫 번째 cycle 출력
         CYCLE += 1;
-        COMMAND = String::from(format!("memory_write {arg1}"));
+        COMMAND = String::from(format!("memory_unlock {arg1}"));
         CYCLE_DONE = true;
         print_cycle();
-        
-        let mut p_index = -1;
-        let mut autho = 0;
-        let mut page = None;   
-        let mut running_pid = 0;
-        let mut v_index = 0;
+        MODE = String::from("kernel");      // 모드 스위칭
+
+        // 2. 두 번째 cycle
+        CYCLE += 1;
+        COMMAND = String::from("system call");
         match &RUNNING {
             None => return,
             Some(r) => {
-                let mut c = r.clone();
-                running_pid = c.pid;
                 for i in 0..32 {
-                    if c.virtual_mem[i].is_none() {continue;}
-                    let target = c.virtual_mem[i].unwrap();
-                    if target.page_id == arg1 as i32 {
-                        p_index = c.page_table[i][1];
-                        v_index = i;
-                        autho = target.authority;
-                        if autho == 1 {     // 권한이 R이었을 경우
-                            c.virtual_mem[i] = Some(authority_change(target, 1));       // 권한 W로 변경
-                        }
-                        page = c.virtual_mem[i];
-                        break;
+                    if let Some(page) = space_of(r.pid).virtual_mem[i] {
+                        if page.allocation_id == arg1 as i32 {PINNED.remove(&(r.pid, i));}
                     }
                 }
-                RUNNING = Some(c);
+                make_ready(r.clone());
+                RUNNING = None;     // running -> ready
             }
         }
+        CYCLE_DONE = true;
+        print_cycle();
 
-        if autho == 0 {     // 권한이 W였을 경우
-            if p_index != -1 {      // 물리메모리에 있는 경우
-                p_mem_count_plus(p_index as u32);
-                if CHANGE_ALGORITHM.contains("lru") {      // 페이지 교체 알고리즘이 lru일 경우 PQ 업데이트
-                    lru_update(PHYSICAL_MEM[p_index as usize].unwrap());
-                }
-                running_process();      // 다음 유저 명령어 실행
-            } else {        // 물리메모리에 없는 경우
-                // 2. 두 번째 cycle : page fault handle
-                CYCLE += 1;
-                COMMAND = String::from("fault");
-                MODE = String::from("kernel");
-                p_index = page_fault_handler(arg1) as i32;     // 물리메모리에 새롭게 할당
-                page_table_frame_add(page.unwrap(), p_index as u32);        // 페이지 테이블 업데이트
-                match &RUNNING {
-                    None => return,
-                    Some(r) => {
-                        RQ.push_back(r.clone());    
-                        RUNNING = None;     // running -> ready
-                    }
-                }
-                CYCLE_DONE = true;
-                print_cycle();
-                // 3. 세 번째 cycle : scheduling 
-                idle_or_schedule();
-                return;
-            }
-        } else {        // 권한이 R이었을 경우
-            CYCLE += 1;
-            COMMAND = String::from("fault");
-            MODE = String::from("kernel");
-            rq_wq_search_and_w_change(page.unwrap().pid, page.unwrap().page_id as u32);
-            if p_index != -1 {
-                PHYSICAL_MEM[p_index as usize] = page;      // 물리메모리에서 기존의 프레임 권한 W로 변경
-            }
-            if page.unwrap().pid != running_pid {       // running process가 자식일 경우
-                let new_page = create_page(running_pid, page.unwrap().page_id, page.unwrap().allocation_id, 0, 1);
-                match &RUNNING {
-                    None => return, 
-                    Some(r) => {
-                        let mut c = r.clone();
-                        c.virtual_mem[v_index as usize] = Some(new_page);
-                        RUNNING = Some(c);
-                    }
-                }
-                p_index = page_fault_handler(arg1) as i32;     // 물리메모리에 새롭게 할당
-                page_table_frame_add(new_page, p_index as u32);        // 페이지 테이블 업데이트
-            } else {        // running process가 부모일 경우
-                if p_index != -1{
-                    p_mem_count_plus(p_index as u32);       // 물리메모리에 존재하면 참조 카운트 +1
-                    if CHANGE_ALGORITHM.contains("lru") {      // 페이지 교체 알고리즘이 lru일 경우 PQ 업데이트
-                        lru_update(PHYSICAL_MEM[p_index as usize].unwrap());
-                    }
-                } else {        // 물리메모리에 존재하지 않을 시
-                    p_index = page_fault_handler(arg1) as i32;     // 물리메모리에 새롭게 할당
-                    page_table_frame_add(page.unwrap(), p_index as u32);        // 페이지 테이블 업데이트
-                }
-            }
-            match &RUNNING {
-                None => return,
-                Some(r) => {
-                    RQ.push_back(r.clone());    
-                    RUNNING = None;     // running -> ready
-                }
-            }
-            CYCLE_DONE = true;
-            print_cycle();
-            // 3. 세 번째 cycle : scheduling 
-            idle_or_schedule();
-            return;
-        }
+        // 3. 세 번째 cycle
+        idle_or_schedule();     // scheduling
+        return;
     }
 }
 
-// 페이지 교체 알고리즘이 LRU일 경우 참조되는 페이지를 인자로 받고 이를 PQ에서 맨 위로 업데이트하는 함수
-fn lru_update(target: Page) {
+// 페이지 (pid, page id)가 위치한 물리메모리 index (없으면 -1)
+fn resident_frame(pid: u32, page_id: i32) -> i32 {
     unsafe {
-        let size = PQ.len();
-        let mut top: Option<Page> = None;
-        for _ in 0..size {
-            let page = PQ.pop_front().unwrap();
-            if page.pid == target.pid && page.page_id == target.page_id {
-                top = Some(page);
+        for i in 0..16 {
+            if let Some(q) = PHYSICAL_MEM[i] {
+                if q.table == -1 && q.pid == pid && q.page_id == page_id {return i as i32;}
             }
-            else {PQ.push_back(page);}
         }
-        PQ.push_back(top.unwrap());
+        -1
     }
 }
 
-// 필요한 페이지의 page id를 인자로 받고 이를 물리 메모리에 할당하는 함수 -> 물리메모리에서의 인덱스 반환
-fn page_fault_handler(page_id: u32) -> u32 {
+// 명령어 shm_create 처리 -> 새 공유 메모리 segment를 만들어 물리메모리에 할당하고 running process에 attach
+// 이미 있는 key이면 해당 segment에 attach만 함
+fn shm_create(key: u32, arg1: u32) {
     unsafe {
-        MODE = String::from("kernel");
-        let p_index = find_physical_space(1).pop().unwrap();       // 필요한 페이지를 할당할 물리 메모리에서의 index
-        match &RUNNING {
-            None => return 0,
-            Some(r) => {
-                for i in 0..32 {
-                    if r.virtual_mem[i].is_none() {continue;}
-                    if r.virtual_mem[i].unwrap().page_id == page_id as i32 {
-                        let new_page = create_page(r.virtual_mem[i].unwrap().pid, page_id as i32, r.virtual_mem[i].unwrap().allocation_id, r.virtual_mem[i].unwrap().authority, 1);
-                        PHYSICAL_MEM[p_index as usize] = Some(new_page);     
-                        if CHANGE_ALGORITHM.contains("fifo") ||  CHANGE_ALGORITHM.contains("lru") {
-                            PQ.push_back(new_page);
-                        }
-                        break;
-                    }
-                }
-            }
-        }
-        return p_index;
-    }
-}
-
-// 명령어 fork_and_exec 처리 
-fn fork_and_exec(name: String) {
-    unsafe{
-        // 1. fork 명령어가 실행된 첫 번째 cycle 출력 
+        // 1. 첫 번째 cycle 출력
         CYCLE += 1;
-        COMMAND = String::from(format!("fork_and_exec {name}"));
+        COMMAND = String::from(format!("shm_create {key} {arg1}"));
         CYCLE_DONE = true;
         print_cycle();
-        MODE = String::from("kernel");
-        
-        // 2. 두 번째 cycle 출력
+        MODE = String::from("kernel");      // 모드 스위칭
+
+        // 2. 두 번째 cycle
         CYCLE += 1;
         COMMAND = String::from("system call");
+        if !SHM.contains_key(&key) && find_virtual_space(arg1) + arg1 <= 32 {
+            let physical_index: Vec<u32> = match find_physical_space(arg1) {
+                None => return,     // 공간을 찾는 도중 OOM killer에 의해 종료됨
+                Some(v) => v,
+            };
+            let mut ids: Vec<i32> = Vec::new();
+            for i in 0..arg1 {
+                SHARED_PAGE_ID += 1;
+                let new_page = create_page(0, SHARED_PAGE_ID, key as i32, PERM_R | PERM_W, false, 1, -1);       // 주인 pid가 0인 공유 페이지
+                let index_p = physical_index[i as usize];
+                PHYSICAL_MEM[index_p as usize] = Some(new_page);
+                if CHANGE_ALGORITHM.contains("fifo") || CHANGE_ALGORITHM.contains("lru") {
+                    PQ.push_back(new_page);
+                }
+                hpt_insert(0, SHARED_PAGE_ID, index_p);
+                ids.push(SHARED_PAGE_ID);
+            }
+            SHM.insert(key, (ids, 0));
+        }
+        if !shm_map(key) {return;}      // running process가 OOM killer로 종료됨
         match &RUNNING {
             None => return,
             Some(r) => {
-                // 새로운 process 생성
-                    // 새로 들어온 process를 읽고 한 줄씩 VecDeque에 저장
-                let process_dir: String = format!("{}\\{}", INPUT_DIR, name).to_string();       
-                let mut lines: VecDeque<String> = VecDeque::new();
-                let file = File::open(process_dir).unwrap();
-                let reader = BufReader::new(file).lines();
-                for line in reader {
-                    lines.push_back(line.unwrap());
-                }
-                PID += 1;
-                let mut new_r = r.clone();
-                // running process의 페이지 모두 권한을 W -> R로 (물리메모리도 수정)
-                for i in 0..32 {
-                    if !new_r.virtual_mem[i].is_none() {
-                        new_r.virtual_mem[i] = Some(authority_change(new_r.virtual_mem[i].unwrap(), 0));     // R로 권한 변경
-                        if new_r.page_table[i][1] != -1 {
-                            let old_page = PHYSICAL_MEM[new_r.page_table[i][1] as usize].unwrap();
-                            PHYSICAL_MEM[new_r.page_table[i][1] as usize] = Some(authority_change(old_page, 0));
-                        }
-                    }
-                    else {continue;}
-                }
-                // 부모 프로세스의 가상 메모리를 CoW
-                let p = create_process(name, PID, new_r.pid, "None".to_string(), lines, new_r.virtual_mem, new_r.page_table, new_r.page_id, new_r.allocation_id);
-                NEWP = Some(p);     // new process 갱신
-                RQ.push_back(new_r.clone());      // 부모 process(현재 running process) ready queue에 넣기
-                RUNNING = None;
+                make_ready(r.clone());
+                RUNNING = None;     // running -> ready
             }
         }
         CYCLE_DONE = true;
         print_cycle();
 
-        // 3. 세 번째 cycle 출력
-            // new 상태의 process ready queue에 넣기
-        match &NEWP {
-            None => return,
-            Some(p) => {
-                RQ.push_back(p.clone());
-                NEWP = None;
-            }
-        } 
+        // 3. 세 번째 cycle
         idle_or_schedule();     // scheduling
         return;
     }
 }
 
-// 명령어 wait 처리
-fn wait() {
-    unsafe{
+// 명령어 shm_attach 처리 -> 이미 있는 공유 메모리 segment를 running process의 가상 메모리에 매핑
+fn shm_attach(key: u32) {
+    unsafe {
         // 1. 첫 번째 cycle 출력
         CYCLE += 1;
-        COMMAND = String::from("wait");
+        COMMAND = String::from(format!("shm_attach {key}"));
         CYCLE_DONE = true;
         print_cycle();
         MODE = String::from("kernel");      // 모드 스위칭
 
-        // 2. 두 번째 cycle 출력
+        // 2. 두 번째 cycle
         CYCLE += 1;
         COMMAND = String::from("system call");
-            // ready queue에 자식 프로세스가 존재하는지 확인
-        let mut find = false;
+        if !shm_map(key) {return;}      // running process가 OOM killer로 종료됨
         match &RUNNING {
             None => return,
-            Some(p) => {
-                for (_, value) in RQ.iter_mut().enumerate() {
-                    if value.ppid == p.pid {      // 자식 프로세스 존재
-                        let mut p1 = p.clone();
-                        p1.status = "W".to_string();
-                        WQ.push_back(p1);
-                        find = true;
-                        break;
-                    }
-                }
-                if !find {      // 자식 프로세스 없음
-                    RQ.push_back(p.clone());
-                }
-                RUNNING = None;
-                CYCLE_DONE = true;
-                print_cycle();
+            Some(r) => {
+                make_ready(r.clone());
+                RUNNING = None;     // running -> ready
             }
         }
+        CYCLE_DONE = true;
+        print_cycle();
 
-        // 3. 세 번째 cycle 출력 
-        idle_or_schedule();
+        // 3. 세 번째 cycle
+        idle_or_schedule();     // scheduling
+        return;
     }
 }
 
-// 명령어 exit 처리
-fn exit() {
-    unsafe{
+// 명령어 shm_detach 처리 -> 공유 메모리 segment의 매핑 해제, 마지막 process가 detach하면 segment 삭제
+fn shm_detach(key: u32) {
+    unsafe {
         // 1. 첫 번째 cycle 출력
         CYCLE += 1;
-        COMMAND = String::from("exit");
+        COMMAND = String::from(format!("shm_detach {key}"));
         CYCLE_DONE = true;
         print_cycle();
         MODE = String::from("kernel");      // 모드 스위칭
 
-        // 2. 두 번째 cycle 출력
+        // 2. 두 번째 cycle
         CYCLE += 1;
         COMMAND = String::from("system call");
+        let allocation_id = match (&RUNNING, SHM.get(&key)) {
+            (Some(r), Some(seg)) => space_of(r.pid).virtual_mem.iter().flatten().find(|q| q.pid == 0 && seg.0.contains(&q.page_id)).map(|q| q.allocation_id),
+            _ => None,
+        };
+        if let Some(a) = allocation_id {
+            release(a as u32);      // segment를 매핑한 allocation 해제 (release에서 참조 수 감소)
+        }
         match &RUNNING {
             None => return,
-            Some(c) => {
-                // 부모 process가 waiting 중인지 확인
-                for (index, value) in WQ.iter_mut().enumerate() {
-                    if value.pid == c.ppid {
-                        RQ.push_back(value.clone());
-                        WQ.remove(index);
-                        break;
-                    }
-                }
-                    // 해당 프로세스의 모든 allocation id에 대해 release
-                let mut al:Vec<u32> = Vec::new();
-                for i in 0..32 {
-                    if !c.virtual_mem[i].is_none() {
-                        let a = c.virtual_mem[i].unwrap().allocation_id as u32;
-                        if !al.contains(&a) {al.push(a);}
-                    }
-                }
-                let size = al.len();
-                for _ in 0..size {
-                    release(al.pop().unwrap());
-                }
-                TERMINATED = Some(c.clone());
-                RUNNING = None;
+            Some(r) => {
+                make_ready(r.clone());
+                RUNNING = None;     // running -> ready
             }
         }
         CYCLE_DONE = true;
         print_cycle();
 
-        // 3. 세 번째 cycle 출력
-        TERMINATED = None;
-        match &NEWP {
-            Some(_) => {            // 종료되지 않은 new process가 존재할 경우
-                idle_or_schedule();      
-            },
-            None => {
-                if RQ.is_empty() && WQ.is_empty() {     // 종료되지 않은 프로세스가 running process 단 하나일 경우
-                    return;
-                } else {        // 종료되지 않은 프로세스가 더 남아있는 경우
-                    idle_or_schedule();
+        // 3. 세 번째 cycle
+        idle_or_schedule();     // scheduling
+        return;
+    }
+}
+
+// 공유 메모리 segment를 running process의 가상 메모리에 새 allocation으로 매핑하고 참조 수 +1
+// segment가 없거나 이미 attach했거나 가상 메모리 공간이 부족하면 아무것도 하지 않음 (테이블 페이지를 불러오는 도중 running process가 OOM killer로 종료되면 false)
+fn shm_map(key: u32) -> bool {
+    unsafe {
+        let ids = match SHM.get(&key) {
+            None => return true,
+            Some(seg) => seg.0.clone(),
+        };
+        let virtual_index = find_virtual_space(ids.len() as u32) as usize;
+        match &RUNNING {
+            None => return true,
+            Some(r) => {
+                let s = space_of(r.pid);
+                if s.virtual_mem.iter().flatten().any(|q| q.pid == 0 && ids.contains(&q.page_id)) {return true;}
+                if virtual_index + ids.len() > 32 {return true;}
+                s.allocation_id += 1;
+                for (i, &id) in ids.iter().enumerate() {
+                    let new_page = create_page(0, id, s.allocation_id, PERM_R | PERM_W, false, 1, -1);
+                    s.virtual_mem[virtual_index + i] = Some(new_page);
+                    s.page_table[virtual_index + i][0] = id;
+                    s.page_table[virtual_index + i][1] = resident_frame(0, id);       // 다른 process가 이미 불러온 프레임을 그대로 가리킴
+                    rmap_add(new_page, r.pid, virtual_index + i);
                 }
             }
         }
+        SHM.get_mut(&key).unwrap().1 += 1;      // 도중에 종료되더라도 종료 처리에서 참조 수가 맞게 줄어들도록 먼저 증가
+        if PT_LEVEL > 1 {       // 다단계 페이지 테이블일 경우 매핑된 페이지들을 담당하는 테이블 페이지 할당
+            for i in 0..ids.len() {
+                if !pt_load(virtual_index + i) {return false;}
+            }
+        }
+        true
     }
 }
 
-// 프로그램 파일 읽고 명령어에 맞게 처리하는 함수
-fn running_process() {
-    unsafe{
-        match &RUNNING {
+// 공유 메모리 segment 참조 수 -1 -> 0이 되면 segment의 페이지를 물리메모리에서 해제하고 segment 삭제
+fn shm_put(key: u32) {
+    unsafe {
+        let seg = match SHM.get_mut(&key) {
             None => return,
-            Some(p) => {
-                let mut v = p.lines.clone();
-                while !v.is_empty() {
-                    MODE = String::from("user");
-                    let order = v.pop_front().unwrap();
-                    if order.contains("exit") {      // 명령어 exit가 들어왔을 경우
-                        exit();
-                        return;
-                    }
-                    // running process의 요소 lines를 이후 남은 명령어들의 queue로 갱신해주기
-                    let mut new_lines: VecDeque<String> = VecDeque::new();
-                    for after in &v {
-                        new_lines.push_back(after.to_string());
-                    }
-                    let c = p.clone();
-                    RUNNING = Some(create_process(c.name, c.pid, c.ppid, "None".to_string(), new_lines, c.virtual_mem, c.page_table, c.page_id, c.allocation_id));
-                    if order.contains("memory_allocate") {      // 명령어 memory_allocate이 들어왔을 경우
-                        let n: u32 = order.trim().split(" ").last().unwrap().parse().unwrap();
-                        memory_allocate(n);
-                        return;
-                    } else if order.contains("memory_release") {        // 명령어 memory_release가 들어왔을 경우
-                        let n: u32 = order.trim().split(" ").last().unwrap().parse().unwrap();
-                        memory_release(n);
-                        return;
-                    } else if order.contains("memory_read") {      // 명령어 memory_read가 들어왔을 경우
-                        let n: u32 = order.trim().split(" ").last().unwrap().parse().unwrap();
-                        memory_read(n);
-                        return;
-                    } else if order.contains("memory_write") {      // 명령어 memory_write가 들어왔을 경우
-                        let n: u32 = order.trim().split(" ").last().unwrap().parse().unwrap();
-                        memory_write(n);
-                        return;
-                    } else if order.contains("fork_and_exec") {       // 명령어 fork가 들어왔을 경우
-                        let name = order.trim().split(" ").last().unwrap().to_string();
-                        fork_and_exec(name);
-                        return;
-                    } else if order.contains("wait") {      // 명령어 wait가 들어왔을 경우
-                        wait();
-                        return;
-                    } else {
-                        println!("wrong order!");
-                        return;
-                    }
+            Some(seg) => seg,
+        };
+        seg.1 -= 1;
+        if seg.1 > 0 {return;}
+        for &id in &seg.0 {
+            let p_index = resident_frame(0, id);
+            if p_index != -1 {
+                if CHANGE_ALGORITHM.contains("fifo") || CHANGE_ALGORITHM.contains("lru") {
+                    remove_PQ(PHYSICAL_MEM[p_index as usize].unwrap());
                 }
+                hpt_remove(0, id);
+                PHYSICAL_MEM[p_index as usize] = None;
             }
         }
+        SHM.remove(&key);
     }
 }
 
-fn main() {     
-    unsafe{
-        let args: Vec<String> = env::args().collect();
-        INPUT_DIR = String::from(&args[1]);    // input file들이 있는 폴더 경로 저장 
-        CHANGE_ALGORITHM = String::from(&args[2]);      // 페이지 교체 알고리즘 저장
-
+// 파일 페이지이면 (파일 이름, 블록 번호) 반환
+fn file_page(page: Page) -> Option<(String, u32)> {
+    unsafe {
+        if page.pid != 0 || page.table != -1 {return None;}
+        FILE_PAGES.get(&page.page_id).cloned()
+    }
+}
+
+// 파일 블록의 페이지 ID 반환 -> 처음 사용되는 블록이면 새 파일 페이지 ID 부여
+fn file_page_id(file: &String, block: u32) -> i32 {
+    unsafe {
+        match FILE_PAGES.iter().find(|(_, f)| f.0 == *file && f.1 == block) {
+            Some((&id, _)) => id,
+            None => {
+                SHARED_PAGE_ID += 1;
+                FILE_PAGES.insert(SHARED_PAGE_ID, (file.clone(), block));
+                SHARED_PAGE_ID
+            }
+        }
+    }
+}
+
+// 파일 블록을 page cache에서 찾고 없으면 파일에서 읽어 물리메모리에 올림 -> 물리메모리 index 반환 (-1이면 running process가 종료됨)
+fn cache_lookup(file: &String, block: u32) -> i32 {
+    unsafe {
+        let id = file_page_id(file, block);
+        let p_index = resident_frame(0, id);
+        if p_index != -1 {      // page cache hit
+            FILE_STATS.0 += 1;
+            file_event(format!("hit {} block {} (frame {})", file, block, p_index));
+            p_mem_count_plus(p_index as u32);
+            if CHANGE_ALGORITHM.contains("lru") {      // 페이지 교체 알고리즘이 lru일 경우 PQ 업데이트
+                lru_update(PHYSICAL_MEM[p_index as usize].unwrap());
+            }
+            return p_index;
+        }
+        // page cache miss: 익명 메모리와 같은 방식으로 프레임을 얻어 파일에서 읽어옴
+        let p_index = match find_physical_space(1) {
+            None => return -1,       // 공간을 찾는 도중 OOM killer에 의해 종료됨
+            Some(mut v) => v.pop().unwrap(),
+        };
+        let new_page = create_page(0, id, -1, PERM_R | PERM_W, false, 1, -1);
+        PHYSICAL_MEM[p_index as usize] = Some(new_page);
+        hpt_insert(0, id, p_index);
+        if CHANGE_ALGORITHM.contains("fifo") || CHANGE_ALGORITHM.contains("lru") {
+            PQ.push_back(new_page);
+        }
+        page_table_frame_add(new_page, p_index);        // mmap으로 매핑한 process들의 페이지 테이블도 업데이트
+        FILE_STATS.1 += 1;
+        file_event(format!("miss {} block {} -> frame {}", file, block, p_index));
+        if let Some(r) = &RUNNING {
+            disk_submit(r.pid, true, 0, id);     // 파일에서 읽는 동안 running process는 대기
+        }
+        p_index as i32
+    }
+}
+
+// 명령어 file_read / file_write 처리 -> page cache를 통해 파일 블록을 읽거나 씀 (쓴 블록은 dirty가 되어 나중에 write-back)
+fn file_access(file: String, block: u32, write: bool) {
+    unsafe {
+        // 1. 첫 번째 cycle 출력
+        CYCLE += 1;
+        COMMAND = if write {format!("file_write {file} {block}")} else {format!("file_read {file} {block}")};
+        CYCLE_DONE = true;
+        print_cycle();
+        MODE = String::from("kernel");      // 모드 스위칭
+
+        // 2. 두 번째 cycle
+        CYCLE += 1;
+        COMMAND = String::from("system call");
+        let command = if write {"file_write"} else {"file_read"};
+        let blocks = file_blocks(&file);
+        if blocks.is_none() {
+            file_event(format!("{} failed: no such file {}", command, file));
+        }
+        else if block >= blocks.unwrap() {     // 파일 끝을 넘는 블록은 없음
+            file_event(format!("{} failed: block {} past end of file", command, block));
+        }
+        else {
+            let p_index = cache_lookup(&file, block);
+            if p_index == -1 {return;}
+            if write {
+                DIRTY.insert(PHYSICAL_MEM[p_index as usize].unwrap().page_id);
+            }
+        }
+        match &RUNNING {
+            None => return,
+            Some(_) => ready_or_block(),     // running -> ready (디스크 읽기를 기다려야 하면 waiting)
+        }
+        CYCLE_DONE = true;
+        print_cycle();
+
+        // 3. 세 번째 cycle
+        idle_or_schedule();     // scheduling
+        return;
+    }
+}
+
+// 이번 cycle의 파일 관련 내용 추가
+fn file_event(event: String) {
+    unsafe {
+        if !FILE_EVENT.is_empty() {FILE_EVENT.push_str(", ");}
+        FILE_EVENT.push_str(&event);
+    }
+}
+
+// dirty인 파일 페이지를 파일에 기록하고 clean으로 표시
+fn write_back(page_id: i32) {
+    unsafe {
+        if !DIRTY.remove(&page_id) {return;}
+        FILE_STATS.2 += 1;
+        let (file, block) = FILE_PAGES[&page_id].clone();
+        file_event(format!("write-back {} block {}", file, block));
+        disk_submit(0, false, 0, page_id);
+    }
+}
+
+// 입력 폴더 파일의 블록 수 (한 줄을 한 블록으로 봄) -> 파일이 없으면 None
+fn file_blocks(file: &String) -> Option<u32> {
+    unsafe {
+        let f = File::open(format!("{}\\{}", INPUT_DIR, file)).ok()?;
+        Some(BufReader::new(f).lines().count() as u32)
+    }
+}
+
+// 명령어 mmap 처리 -> 입력 폴더의 파일을 블록 단위로 running process의 가상 메모리에 매핑 (페이지는 접근할 때 파일에서 읽어옴)
+// 같은 파일 블록을 매핑한 process들은 같은 프레임을 공유함
+fn mmap(file: String, arg1: u32) {
+    unsafe {
+        // 1. 첫 번째 cycle 출력
+        CYCLE += 1;
+        COMMAND = String::from(format!("mmap {file} {arg1}"));
+        CYCLE_DONE = true;
+        print_cycle();
+        MODE = String::from("kernel");      // 모드 스위칭
+
+        // 2. 두 번째 cycle
+        CYCLE += 1;
+        COMMAND = String::from("system call");
+        let virtual_index = find_virtual_space(arg1) as usize;
+        let blocks = file_blocks(&file);
+        if blocks.is_none() {
+            file_event(format!("mmap failed: no such file {}", file));
+        }
+        else if arg1 > blocks.unwrap() {        // 파일 끝을 넘는 블록은 매핑하지 않음
+            file_event(format!("mmap failed: {} has only {} blocks", file, blocks.unwrap()));
+        }
+        else if virtual_index + arg1 as usize <= 32 {
+            match &RUNNING {
+                None => return,
+                Some(r) => {
+                    let s = space_of(r.pid);
+                    s.allocation_id += 1;
+                    for block in 0..arg1 {
+                        let id = file_page_id(&file, block);
+                        let index_v = virtual_index + block as usize;
+                        let new_page = create_page(0, id, s.allocation_id, PERM_R | PERM_W, false, 1, -1);
+                        s.virtual_mem[index_v] = Some(new_page);
+                        s.page_table[index_v][0] = id;
+                        s.page_table[index_v][1] = resident_frame(0, id);       // 다른 process가 이미 불러온 프레임을 그대로 가리킴
+                        rmap_add(new_page, r.pid, index_v);
+                    }
+                }
+            }
+            if PT_LEVEL > 1 {       // 다단계 페이지 테이블일 경우 매핑된 페이지들을 담당하는 테이블 페이지 할당
+                for i in 0..arg1 as usize {
+                    if !pt_load(virtual_index + i) {return;}        // 도중에 OOM killer로 종료되면 중단
+                }
+            }
+        }
+        match &RUNNING {
+            None => return,
+            Some(r) => {
+                make_ready(r.clone());
+                RUNNING = None;     // running -> ready
+            }
+        }
+        CYCLE_DONE = true;
+        print_cycle();
+
+        // 3. 세 번째 cycle
+        idle_or_schedule();     // scheduling
+        return;
+    }
+}
+
+// 명령어 munmap 처리 -> 파일 매핑 해제, 수정된 페이지는 파일에 write-back (프레임은 page cache에 남음)
+fn munmap(file: String) {
+    unsafe {
+        // 1. 첫 번째 cycle 출력
+        CYCLE += 1;
+        COMMAND = String::from(format!("munmap {file}"));
+        CYCLE_DONE = true;
+        print_cycle();
+        MODE = String::from("kernel");      // 모드 스위칭
+
+        // 2. 두 번째 cycle
+        CYCLE += 1;
+        COMMAND = String::from("system call");
+        let allocation_id = match &RUNNING {
+            None => return,
+            Some(r) => space_of(r.pid).virtual_mem.iter().flatten().find(|q| matches!(file_page(**q), Some((f, _)) if f == file)).map(|q| q.allocation_id),
+        };
+        if let Some(a) = allocation_id {
+            release(a as u32);      // 파일을 매핑한 allocation 해제 (release에서 write-back)
+        }
+        match &RUNNING {
+            None => return,
+            Some(r) => {
+                make_ready(r.clone());
+                RUNNING = None;     // running -> ready
+            }
+        }
+        CYCLE_DONE = true;
+        print_cycle();
+
+        // 3. 세 번째 cycle
+        idle_or_schedule();     // scheduling
+        return;
+    }
+}
+
+// 경로(/로 구분)를 따라가 inode 번호 반환 -> 없으면 None
+fn fs_lookup(path: &str) -> Option<u32> {
+    unsafe {
+        let mut ino = 0;
+        for name in path.split('/').filter(|n| !n.is_empty()) {
+            let node = INODES.get(&ino)?;
+            if !node.dir {return None;}
+            ino = *node.entries.get(name)?;
+        }
+        Some(ino)
+    }
+}
+
+// 경로의 부모 디렉터리 inode 번호와 마지막 이름 반환 -> 부모 디렉터리가 없으면 None
+fn fs_parent(path: &str) -> Option<(u32, String)> {
+    unsafe {
+        let trimmed = path.trim_end_matches('/');
+        let (dir, name) = match trimmed.rsplit_once('/') {
+            None => ("", trimmed),
+            Some(dn) => dn,
+        };
+        if name.is_empty() {return None;}
+        let parent = fs_lookup(dir)?;
+        if !INODES[&parent].dir {return None;}
+        Some((parent, name.to_string()))
+    }
+}
+
+// 부모 디렉터리에 새 inode를 만들어 연결 -> 새 inode 번호 반환
+fn fs_create(parent: u32, name: String, dir: bool) -> u32 {
+    unsafe {
+        let ino = NEXT_INO;
+        NEXT_INO += 1;
+        INODES.insert(ino, Inode {dir, size: 0, links: 1, entries: BTreeMap::new()});
+        INODES.get_mut(&parent).unwrap().entries.insert(name, ino);
+        ino
+    }
+}
+
+// 연결된 디렉터리 항목도, 열려 있는 fd도 없는 inode를 해제하고 page cache에 남은 블록도 버림
+fn fs_free(ino: u32) {
+    unsafe {
+        if INODES[&ino].links > 0 || OPEN_FILES.values().any(|f| f.0 == ino) {return;}
+        INODES.remove(&ino);
+        let name = format!("#{}", ino);
+        let ids: Vec<i32> = FILE_PAGES.iter().filter(|(_, f)| f.0 == name).map(|(&id, _)| id).collect();
+        for id in ids {
+            let p_index = resident_frame(0, id);
+            if p_index != -1 {
+                if CHANGE_ALGORITHM.contains("fifo") || CHANGE_ALGORITHM.contains("lru") {
+                    remove_PQ(PHYSICAL_MEM[p_index as usize].unwrap());
+                }
+                hpt_remove(0, id);
+                PHYSICAL_MEM[p_index as usize] = None;
+            }
+            DIRTY.remove(&id);
+            FILE_PAGES.remove(&id);
+        }
+    }
+}
+
+// 열린 파일 테이블 항목의 참조 수 -1 -> 0이 되면 항목을 지우고 inode 해제 여부 확인
+fn fs_close(i: usize) {
+    unsafe {
+        let entry = OPEN_FILES.get_mut(&i).unwrap();
+        entry.3 -= 1;
+        if entry.3 > 0 {return;}
+        let ino = entry.0;
+        OPEN_FILES.remove(&i);
+        fs_free(ino);
+    }
+}
+
+// 이번 cycle의 파일시스템 명령어 결과 추가
+fn fs_event(event: String) {
+    unsafe {
+        if !FS_EVENT.is_empty() {FS_EVENT.push_str(", ");}
+        FS_EVENT.push_str(&event);
+    }
+}
+
+// 파일시스템 명령어(open / close / read / write / mkdir / unlink) 처리
+// open <경로>: 파일을 열고(없으면 생성) 가장 작은 빈 fd 할당 / close <fd>: fd 닫기
+// read <fd> <n>, write <fd> <n>: offset부터 n 블록을 page cache를 통해 읽거나 쓰고 offset 이동
+// mkdir <경로>: 디렉터리 생성 / unlink <경로>: 파일의 디렉터리 항목 삭제 (열려 있으면 모두 닫힐 때 해제)
+fn fs_call(args: Vec<String>) {
+    unsafe {
+        // 1. 첫 번째 cycle 출력
+        CYCLE += 1;
+        COMMAND = args.join(" ");
+        CYCLE_DONE = true;
+        print_cycle();
+        MODE = String::from("kernel");      // 모드 스위칭
+
+        // 2. 두 번째 cycle
+        CYCLE += 1;
+        COMMAND = String::from("system call");
+        let arg = args.get(1).cloned().unwrap_or_default();
+        match args[0].as_str() {
+            "open" => {
+                let ino = match fs_lookup(&arg) {
+                    Some(ino) => Some(ino),
+                    None => fs_parent(&arg).map(|(parent, name)| fs_create(parent, name, false)),
+                };
+                match ino {
+                    Some(ino) if !INODES[&ino].dir => {
+                        if let Some(r) = RUNNING.as_mut() {
+                            let fds = &mut entry_of(r.pid).fds;
+                            let fd = (0..).find(|fd| !fds.contains_key(fd)).unwrap();
+                            OPEN_FILES.insert(NEXT_OPEN, (ino, arg.clone(), 0, 1));
+                            fds.insert(fd, NEXT_OPEN);
+                            NEXT_OPEN += 1;
+                            fs_event(format!("open {} -> fd {}", arg, fd));
+                        }
+                    }
+                    _ => fs_event(format!("open failed: {}", arg)),
+                }
+            }
+            "close" => {
+                let fd: u32 = arg.parse().unwrap();
+                match RUNNING.as_ref().and_then(|r| entry_of(r.pid).fds.remove(&fd)) {
+                    None => fs_event(format!("close failed: bad fd {}", fd)),
+                    Some(i) => {
+                        fs_close(i);
+                        fs_event(format!("close fd {}", fd));
+                    }
+                }
+            }
+            "read" | "write" => {
+                let write = args[0] == "write";
+                let fd: u32 = arg.parse().unwrap();
+                let n: u32 = args.last().unwrap().parse().unwrap();
+                let i = match &RUNNING {
+                    None => return,
+                    Some(r) => entry_of(r.pid).fds.get(&fd).cloned(),
+                };
+                match i {
+                    None => fs_event(format!("{} failed: bad fd {}", args[0], fd)),
+                    Some(i) => {
+                        let (ino, _, offset, _) = OPEN_FILES[&i].clone();
+                        let end = if write {offset + n} else {(offset + n).min(INODES[&ino].size)};     // 읽기는 파일 끝까지만
+                        for block in offset..end {
+                            let p_index = cache_lookup(&format!("#{}", ino), block);
+                            if p_index == -1 {return;}      // 불러오는 도중 OOM killer에 의해 종료됨
+                            if write {DIRTY.insert(PHYSICAL_MEM[p_index as usize].unwrap().page_id);}
+                        }
+                        if let Some(f) = OPEN_FILES.get_mut(&i) {f.2 = end;}
+                        if let Some(node) = INODES.get_mut(&ino) {node.size = node.size.max(end);}
+                        fs_event(format!("{} fd {}: {} blocks", args[0], fd, end - offset));
+                    }
+                }
+            }
+            "mkdir" => {
+                match fs_parent(&arg) {
+                    Some((parent, name)) if !INODES[&parent].entries.contains_key(&name) => {
+                        fs_create(parent, name, true);
+                        fs_event(format!("mkdir {}", arg));
+                    }
+                    _ => fs_event(format!("mkdir failed: {}", arg)),
+                }
+            }
+            _ => {      // unlink
+                match (fs_parent(&arg), fs_lookup(&arg)) {
+                    (Some((parent, name)), Some(ino)) if !INODES[&ino].dir => {
+                        INODES.get_mut(&parent).unwrap().entries.remove(&name);
+                        INODES.get_mut(&ino).unwrap().links -= 1;
+                        fs_free(ino);
+                        fs_event(format!("unlink {}", arg));
+                    }
+                    _ => fs_event(format!("unlink failed: {}", arg)),
+                }
+            }
+        }
+        match &RUNNING {
+            None => return,
+            Some(_) => ready_or_block(),     // running -> ready (디스크 읽기를 기다려야 하면 waiting)
+        }
+        CYCLE_DONE = true;
+        print_cycle();
+
+        // 3. 세 번째 cycle
+        idle_or_schedule();     // scheduling
+        return;
+    }
+}
+
+// 파일시스템 초기화: 루트 디렉터리 생성 후 이미지 파일이 있으면 한 줄씩 읽어 디렉터리(d 경로)와 파일(f 경로 크기) 생성
+fn fs_load() {
+    unsafe {
+        INODES.insert(0, Inode {dir: true, size: 0, links: 1, entries: BTreeMap::new()});
+        if FS_IMAGE.is_empty() {return;}
+        let file = match File::open(&FS_IMAGE) {
+            Err(_) => return,       // 이미지 파일이 없으면 빈 파일시스템으로 시작
+            Ok(f) => f,
+        };
+        for line in BufReader::new(file).lines() {
+            let line = line.unwrap();
+            let fields: Vec<&str> = line.split(' ').collect();
+            if fields.len() < 2 {continue;}
+            if let Some((parent, name)) = fs_parent(fields[1]) {
+                let ino = fs_create(parent, name, fields[0] == "d");
+                if fields[0] == "f" && fields.len() > 2 {
+                    INODES.get_mut(&ino).unwrap().size = fields[2].parse().unwrap();
+                }
+            }
+        }
+    }
+}
+
+// 모든 cycle이 끝난 뒤 파일시스템을 이미지 파일에 저장 (부모 디렉터리가 먼저 오도록 깊이 우선 순서)
+fn fs_save() {
+    unsafe {
+        if FS_IMAGE.is_empty() {return;}
+        let mut temp = String::new();
+        let mut stack: Vec<(u32, String)> = vec![(0, String::new())];
+        while let Some((ino, path)) = stack.pop() {
+            for (name, &child) in INODES[&ino].entries.iter().rev() {
+                let child_path = format!("{}/{}", path, name);
+                if INODES[&child].dir {
+                    temp.push_str(&format!("d {}\n", child_path));
+                    stack.push((child, child_path));
+                }
+                else {
+                    temp.push_str(&format!("f {} {}\n", child_path, INODES[&child].size));
+                }
+            }
+        }
+        let mut image = File::create(&FS_IMAGE).expect("create failed");
+        image.write_all(temp.as_bytes()).expect("write failed");
+    }
+}
+
+// 인자로 페이지를 받고, 페이지의 CoW 여부만 변경해서 새 페이지를 반환하는 함수 (접근 권한은 그대로)
+fn cow_change(old_page: Page, cow: bool) -> Page {
+    return create_page(old_page.pid, old_page.page_id, old_page.allocation_id, old_page.perms, cow, old_page.count, old_page.table);
+}
+
+// 권한 비트를 rwx 형식 문자열로 반환 -> CoW 공유 중이면 뒤에 c를 붙임 (예: rw-, rw-c, r-x)
+fn perm_string(perms: u32, cow: bool) -> String {
+    let mut s = String::new();
+    s.push(if perms & PERM_R != 0 {'r'} else {'-'});
+    s.push(if perms & PERM_W != 0 {'w'} else {'-'});
+    s.push(if perms & PERM_X != 0 {'x'} else {'-'});
+    if cow {s.push('c');}
+    s
+}
+
+// mprotect 권한 인자(r, w, x, - 조합)를 권한 비트로 변환 -> 잘못된 문자가 있으면 None
+fn parse_perms(arg: &str) -> Option<u32> {
+    let mut perms = 0;
+    for ch in arg.chars() {
+        match ch {
+            'r' => perms |= PERM_R,
+            'w' => perms |= PERM_W,
+            'x' => perms |= PERM_X,
+            '-' => {},
+            _ => return None,
+        }
+    }
+    Some(perms)
+}
+
+// 인자로 물리메모리에서의 인덱스를 받고, 해당 위치 페이지의 참조 카운트 +1 하기
+fn p_mem_count_plus(p_index: u32) {
+    unsafe {
+        let old_page = PHYSICAL_MEM[p_index as usize].unwrap();
+        let new_page = create_page(old_page.pid, old_page.page_id, old_page.allocation_id, old_page.perms, old_page.cow, old_page.count+1, old_page.table);
+        PHYSICAL_MEM[p_index as usize] = Some(new_page);
+    }
+}
+
+// 프로세스의 가상 메모리 index에 해당하는 물리메모리 index와 탐색 횟수 반환 (없으면 -1)
+fn lookup(p: &Process, v_index: usize) -> (i32, i32) {
+    unsafe {
+        let page = match space_of(p.pid).virtual_mem[v_index] {
+            None => return (-1, 1),
+            Some(page) => page,
+        };
+        let mut probes = 0;
+        if TRANSLATION.contains("inverted") {       // 역 페이지 테이블: 프레임 순서대로 (pid, page id) 비교
+            for i in 0..16 {
+                probes += 1;
+                if let Some(q) = PHYSICAL_MEM[i] {
+                    if q.table == -1 && q.pid == page.pid && q.page_id == page.page_id {
+                        return (i as i32, probes);
+                    }
+                }
+            }
+            (-1, probes)
+        }
+        else if TRANSLATION.contains("hashed") {      // 해시 페이지 테이블: 해당 버킷의 체인만 비교
+            for e in &HPT[hpt_hash(page.pid, page.page_id)] {
+                probes += 1;
+                if e[0] == page.pid as i32 && e[1] == page.page_id {
+                    return (e[2], probes);
+                }
+            }
+            (-1, probes.max(1))
+        }
+        else {
+            (space_of(p.pid).page_table[v_index][1], 1)
+        }
+    }
+}
+
+// 프로세스의 가상 메모리 index에 해당하는 물리메모리 index 반환 (없으면 -1)
+fn frame_of(p: &Process, v_index: usize) -> i32 {
+    lookup(p, v_index).0
+}
+
+// running process가 page id에 접근할 때의 주소 변환 탐색 횟수 기록
+fn translate_probe(page_id: u32) {
+    unsafe {
+        if TRANSLATION.is_empty() {return;}
+        PROBES = -1;        // 이전 접근의 탐색 횟수는 버림
+        PROBE_CYCLE = CYCLE;
+        if let Some(r) = &RUNNING {
+            for i in 0..32 {
+                if !space_of(r.pid).virtual_mem[i].is_none() && space_of(r.pid).virtual_mem[i].unwrap().page_id == page_id as i32 {
+                    PROBES = lookup(r, i).1;
+                    PROBE_TOTAL += PROBES as u32;
+                    ACCESS_TOTAL += 1;
+                    break;
+                }
+            }
+        }
+    }
+}
+
+// 해시 페이지 테이블의 버킷 번호 계산
+fn hpt_hash(pid: u32, page_id: i32) -> usize {
+    (pid as usize * 31 + page_id as usize) % HPT_SIZE
+}
+
+// 해시 페이지 테이블에 (pid, page id) -> 물리메모리 index 추가
+fn hpt_insert(pid: u32, page_id: i32, p_index: u32) {
+    unsafe {
+        if !TRANSLATION.contains("hashed") {return;}
+        hpt_remove(pid, page_id);
+        HPT[hpt_hash(pid, page_id)].push([pid as i32, page_id, p_index as i32]);
+    }
+}
+
+// 해시 페이지 테이블에서 (pid, page id) 제거
+fn hpt_remove(pid: u32, page_id: i32) {
+    unsafe {
+        if !TRANSLATION.contains("hashed") {return;}
+        HPT[hpt_hash(pid, page_id)].retain(|e| !(e[0] == pid as i32 && e[1] == page_id));
+    }
+}
+
+// running process의 가상 메모리 index에 해당하는 물리메모리 index 반환 (없으면 -1)
+fn running_frame(v_index: usize) -> i32 {
+    unsafe {
+        match &RUNNING {
+            None => -1,
+            Some(r) => frame_of(r, v_index),
+        }
+    }
+}
+
+// 명령어 memory_read 처리
+fn memory_read(arg1: u32) {
+    unsafe {
+        // 1. 첫 번째 cycle: 읽기 시도
+        CYCLE += 1;
+        COMMAND = String::from(format!("memory_read {arg1}"));
+        translate_probe(arg1);
+        // 물리메모리에 해당 페이지가 존재하는지 알아보기
+        let mut p_index = -1;
+        let mut target = None;      // read하고자 하는 페이지 저장
+        let mut v_index = 0;
+        match &RUNNING {
+            None => return,
+            Some(r) => {
+                for i in 0..32 {
+                    if !space_of(r.pid).virtual_mem[i].is_none() && space_of(r.pid).virtual_mem[i].unwrap().page_id == arg1 as i32 {
+                        p_index = frame_of(r, i);   // 물리메모리에서의 위치
+                        target = space_of(r.pid).virtual_mem[i];      
+                        v_index = i;
+                        break;
+                    }
+                }
+            }
+        }
+        if let Some(t) = target {
+            if t.perms & PERM_R == 0 {      // 읽기 권한이 없으면 protection fault
+                CYCLE_DONE = true;
+                print_cycle();
+                protection_fault("read", t);
+                return;
+            }
+        }
+        let table_hit = pt_resident(v_index);      // 다단계 페이지 테이블일 경우 경로의 테이블 페이지가 모두 물리메모리에 있는지
+        ws_record(arg1 as i32, !(p_index != -1 && table_hit));
+        if p_index != -1 && table_hit {
+            pt_touch(v_index);
+            p_mem_count_plus(p_index as u32);     // 참조 카운트 +1
+            if CHANGE_ALGORITHM.contains("lru") {      // 페이지 교체 알고리즘이 lru일 경우 PQ 업데이트
+                lru_update(PHYSICAL_MEM[p_index as usize].unwrap());
+            }
+            CYCLE_DONE = true;
+            print_cycle();
+            running_process();      // 다음 프로세스 명령어 실행
+            return;
+        }
+        // 물리메모리에 존재하지 않을 경우
+        CYCLE_DONE = true;
+        print_cycle();
+        // 2. 두 번째 cycle: 페이지 폴트 핸들러
+        CYCLE += 1;
+        COMMAND = String::from("fault");
+        MODE = String::from("kernel");      // 모드 스위칭
+        if !table_hit {
+            if !pt_load(v_index) {return;}       // 테이블 페이지 폴트 처리 (도중에 OOM killer로 종료되면 중단)
+            p_index = running_frame(v_index);
+        }
+        if p_index == -1 {
+            p_index = match page_fault_handler(arg1) {
+                None => return,     // 공간을 찾는 도중 OOM killer에 의해 종료됨
+                Some(i) => i as i32,
+            };
+            // 물리메모리에 새로 할당 후 페이지 테이블 업데이트
+            page_table_frame_add(target.unwrap(), p_index as u32);
+        }
+        match &RUNNING {
+            None => return,
+            Some(_) => ready_or_block(),     // running -> ready (디스크 읽기를 기다려야 하면 waiting)
+        }
+        CYCLE_DONE = true;
+        print_cycle();
+        // 3. 세 번째 cycle
+        idle_or_schedule();     // scheduling
+        return;
+    }
+}
+
+// 해당 페이지를 갖는 페이지 테이블 업데이트 -> arg1이 page ID인 곳에 새로운 물리메모리 인덱스 (idx) 넣기
+fn page_table_frame_add(target: Page, idx: u32) {
+    unsafe {
+        if !TRANSLATION.is_empty() {return;}        // 역/해시 페이지 테이블은 page_fault_handler에서 이미 갱신됨
+        // 역매핑으로 해당 페이지를 갖는 프로세스들만 페이지 테이블 업데이트
+        for (pid, i) in rmap_mappers(target.pid, target.page_id) {
+            if let Some(s) = SPACES.get_mut(&pid) {s.page_table[i][1] = idx as i32;}
+        }
+    }
+}
+
+// 명령어 memory_write 처리 함수
+fn memory_write(arg1: u32) {
+    unsafe {
+        // 1. 첫 번째 cycle : 유저 모드
+        CYCLE += 1;
+        COMMAND = String::from(format!("memory_write {arg1}"));
+        translate_probe(arg1);
+        CYCLE_DONE = true;
+        print_cycle();
+        
+        let mut p_index = -1;
+        let mut cow = false;
+        let mut perms = 0;
+        let mut page = None;   
+        let mut running_pid = 0;
+        let mut v_index = 0;
+        match &RUNNING {
+            None => return,
+            Some(r) => {
+                running_pid = r.pid;
+                let s = space_of(r.pid);
+                for i in 0..32 {
+                    if s.virtual_mem[i].is_none() {continue;}
+                    let target = s.virtual_mem[i].unwrap();
+                    if target.page_id == arg1 as i32 {
+                        p_index = frame_of(r, i);
+                        v_index = i;
+                        cow = target.cow;
+                        perms = target.perms;
+                        if cow && perms & PERM_W != 0 {     // CoW 공유 중이었을 경우
+                            s.virtual_mem[i] = Some(cow_change(target, false));       // CoW 해제
+                        }
+                        page = s.virtual_mem[i];
+                        break;
+                    }
+                }
+            }
+        }
+
+        let table_hit = pt_resident(v_index);      // 다단계 페이지 테이블일 경우 경로의 테이블 페이지가 모두 물리메모리에 있는지
+        if perms & PERM_W == 0 {        // 쓰기 권한이 없으면 protection fault
+            protection_fault("write", page.unwrap());
+            return;
+        }
+        if file_page(page.unwrap()).is_some() {        // 파일 페이지는 dirty로 표시 (내보내거나 munmap할 때 write-back)
+            DIRTY.insert(page.unwrap().page_id);
+        }
+        ws_record(arg1 as i32, !(!cow && p_index != -1 && table_hit));
+        if !cow {     // CoW 공유 중이 아닐 경우
+            if p_index != -1 && table_hit {      // 물리메모리에 있는 경우
+                pt_touch(v_index);
+                p_mem_count_plus(p_index as u32);
+                if CHANGE_ALGORITHM.contains("lru") {      // 페이지 교체 알고리즘이 lru일 경우 PQ 업데이트
+                    lru_update(PHYSICAL_MEM[p_index as usize].unwrap());
+                }
+                running_process();      // 다음 유저 명령어 실행
+            } else {        // 물리메모리에 없는 경우
+                // 2. 두 번째 cycle : page fault handle
+                CYCLE += 1;
+                COMMAND = String::from("fault");
+                MODE = String::from("kernel");
+                if !table_hit {
+                    if !pt_load(v_index) {return;}       // 테이블 페이지 폴트 처리 (도중에 OOM killer로 종료되면 중단)
+                    p_index = running_frame(v_index);
+                }
+                if p_index == -1 {
+                    p_index = match page_fault_handler(arg1) {     // 물리메모리에 새롭게 할당
+                        None => return,     // 공간을 찾는 도중 OOM killer에 의해 종료됨
+                        Some(i) => i as i32,
+                    };
+                    page_table_frame_add(page.unwrap(), p_index as u32);        // 페이지 테이블 업데이트
+                }
+                match &RUNNING {
+                    None => return,
+                    Some(_) => ready_or_block(),     // running -> ready (디스크 읽기를 기다려야 하면 waiting)
+                }
+                CYCLE_DONE = true;
+                print_cycle();
+                // 3. 세 번째 cycle : scheduling 
+                idle_or_schedule();
+                return;
+            }
+        } else {        // CoW 공유 중이었을 경우
+            CYCLE += 1;
+            COMMAND = String::from("fault");
+            MODE = String::from("kernel");
+            if !table_hit {
+                if !pt_load(v_index) {return;}       // 테이블 페이지 폴트 처리 (도중에 OOM killer로 종료되면 중단)
+                p_index = running_frame(v_index);
+            }
+            rq_wq_search_and_w_change(page.unwrap().pid, page.unwrap().page_id as u32);
+            if p_index != -1 {
+                PHYSICAL_MEM[p_index as usize] = page;      // 물리메모리에서 기존의 프레임 CoW 해제
+            }
+            if page.unwrap().pid != running_pid {       // running process가 자식일 경우
+                let new_page = create_page(running_pid, page.unwrap().page_id, page.unwrap().allocation_id, page.unwrap().perms, false, 1, -1);
+                let s = space_of(running_pid);
+                s.virtual_mem[v_index as usize] = Some(new_page);
+                s.page_table[v_index as usize][1] = -1;     // 새 프레임을 받기 전까지 부모의 프레임을 가리키지 않음
+                rmap_remove(page.unwrap(), running_pid, v_index);      // 부모 페이지 대신 새 페이지를 매핑
+                rmap_add(new_page, running_pid, v_index);
+                p_index = match page_fault_handler(arg1) {     // 물리메모리에 새롭게 할당
+                    None => return,     // 공간을 찾는 도중 OOM killer에 의해 종료됨
+                    Some(i) => i as i32,
+                };
+                page_table_frame_add(new_page, p_index as u32);        // 페이지 테이블 업데이트
+            } else {        // running process가 부모일 경우
+                if p_index != -1{
+                    p_mem_count_plus(p_index as u32);       // 물리메모리에 존재하면 참조 카운트 +1
+                    if CHANGE_ALGORITHM.contains("lru") {      // 페이지 교체 알고리즘이 lru일 경우 PQ 업데이트
+                        lru_update(PHYSICAL_MEM[p_index as usize].unwrap());
+                    }
+                } else {        // 물리메모리에 존재하지 않을 시
+                    p_index = match page_fault_handler(arg1) {     // 물리메모리에 새롭게 할당
+                        None => return,     // 공간을 찾는 도중 OOM killer에 의해 종료됨
+                        Some(i) => i as i32,
+                    };
+                    page_table_frame_add(page.unwrap(), p_index as u32);        // 페이지 테이블 업데이트
+                }
+            }
+            match &RUNNING {
+                None => return,
+                Some(_) => ready_or_block(),     // running -> ready (디스크 읽기를 기다려야 하면 waiting)
+            }
+            CYCLE_DONE = true;
+            print_cycle();
+            // 3. 세 번째 cycle : scheduling 
+            idle_or_schedule();
+            return;
+        }
+    }
+}
+
+// 권한이 없는 접근으로 protection fault 발생 -> running process를 exit 경로로 종료시키고 다음 process 스케줄
+fn protection_fault(access: &str, page: Page) {
+    unsafe {
+        CYCLE += 1;
+        COMMAND = String::from("protection fault");
+        MODE = String::from("kernel");      // 모드 스위칭
+        if let Some(r) = &RUNNING {
+            PROT_EVENT = format!("{}({}) {} page {} ({})", r.pid, r.name, access, page.page_id, perm_string(page.perms, page.cow));
+        }
+        terminate_running(128 + SIGSEGV as i32, true);      // protection fault는 SIGSEGV로 종료시킨 것으로 취급
+        CYCLE_DONE = true;
+        print_cycle();
+        if NEWP.is_none() && RQ.is_empty() && WQ.is_empty() && SQ.is_empty() && IO_QUEUES.is_empty() {return;}     // 남은 process가 없음
+        idle_or_schedule();
+    }
+}
+
+// 명령어 mprotect 처리 -> running process가 매핑한 allocation의 모든 페이지 접근 권한 변경
+fn mprotect(arg1: u32, perms: u32) {
+    unsafe {
+        // 1. 첫 번째 cycle 출력
+        CYCLE += 1;
+        COMMAND = String::from(format!("mprotect {arg1} {}", perm_string(perms, false)));
+        CYCLE_DONE = true;
+        print_cycle();
+        MODE = String::from("kernel");      // 모드 스위칭
+
+        // 2. 두 번째 cycle
+        CYCLE += 1;
+        COMMAND = String::from("system call");
+        match &RUNNING {
+            None => return,
+            Some(r) => {
+                let c = r.clone();
+                for i in 0..32 {
+                    if let Some(page) = space_of(c.pid).virtual_mem[i] {
+                        if page.allocation_id == arg1 as i32 {      // 자신의 매핑만 변경 (CoW 공유 중인 다른 process의 권한은 그대로)
+                            space_of(c.pid).virtual_mem[i] = Some(create_page(page.pid, page.page_id, page.allocation_id, perms, page.cow, page.count, page.table));
+                        }
+                    }
+                }
+                make_ready(c);
+                RUNNING = None;     // running -> ready
+            }
+        }
+        CYCLE_DONE = true;
+        print_cycle();
+
+        // 3. 세 번째 cycle
+        idle_or_schedule();     // scheduling
+        return;
+    }
+}
+
+// 페이지 교체 알고리즘이 LRU일 경우 참조되는 페이지를 인자로 받고 이를 PQ에서 맨 위로 업데이트하는 함수
+fn lru_update(target: Page) {
+    unsafe {
+        let size = PQ.len();
+        let mut top: Option<Page> = None;
+        for _ in 0..size {
+            let page = PQ.pop_front().unwrap();
+            if page.pid == target.pid && page.page_id == target.page_id && page.table == target.table {
+                top = Some(page);
+            }
+            else {PQ.push_back(page);}
+        }
+        PQ.push_back(top.unwrap());
+    }
+}
+
+// 필요한 페이지의 page id를 인자로 받고 이를 물리 메모리에 할당하는 함수 -> 물리메모리에서의 인덱스 반환
+fn page_fault_handler(page_id: u32) -> Option<u32> {
+    unsafe {
+        MODE = String::from("kernel");
+        SWAP_PENDING = 1;
+        let p_index = find_physical_space(1).map(|mut v| v.pop().unwrap());       // 필요한 페이지를 할당할 물리 메모리에서의 index
+        SWAP_PENDING = 0;
+        let p_index = match p_index {
+            None => return None,        // 공간을 찾는 도중 running process가 OOM killer로 종료됨
+            Some(i) => i,
+        };
+        match &RUNNING {
+            None => return None,
+            Some(r) => {
+                for i in 0..32 {
+                    if space_of(r.pid).virtual_mem[i].is_none() {continue;}
+                    if space_of(r.pid).virtual_mem[i].unwrap().page_id == page_id as i32 {
+                        let new_page = create_page(space_of(r.pid).virtual_mem[i].unwrap().pid, page_id as i32, space_of(r.pid).virtual_mem[i].unwrap().allocation_id, space_of(r.pid).virtual_mem[i].unwrap().perms, space_of(r.pid).virtual_mem[i].unwrap().cow, 1, -1);
+                        PHYSICAL_MEM[p_index as usize] = Some(new_page);     
+                        hpt_insert(new_page.pid, new_page.page_id, p_index);
+                        if let Some((file, block)) = file_page(new_page) {      // 파일 페이지는 파일에서 읽어옴
+                            FILE_STATS.1 += 1;
+                            file_event(format!("miss {} block {} -> frame {}", file, block, p_index));
+                            disk_submit(r.tid, true, 0, page_id as i32);
+                        }
+                        else if SWAP_SLOTS.contains(&(new_page.pid, new_page.page_id)) {      // swap 공간에서 불러오는 페이지
+                            disk_submit(r.tid, true, new_page.pid, new_page.page_id);
+                        }
+                        if CHANGE_ALGORITHM.contains("fifo") ||  CHANGE_ALGORITHM.contains("lru") {
+                            PQ.push_back(new_page);
+                        }
+                        break;
+                    }
+                }
+            }
+        }
+        return Some(p_index);
+    }
+}
+
+// running process의 메모리 참조를 working set window에 기록
+fn ws_record(page_id: i32, fault: bool) {
+    unsafe {
+        if WS_WINDOW == 0 {return;}
+        if let Some(r) = RUNNING.as_mut() {
+            let refs = &mut space_of(r.pid).ws_refs;
+            refs.push_back((page_id, fault));
+            while refs.len() > WS_WINDOW {
+                refs.pop_front();
+            }
+            let wss = working_set(r);
+            let stat = WS_STATS.entry(r.pid).or_insert((r.name.clone(), 0, 0, 0));
+            stat.1 += 1;
+            if fault {stat.2 += 1;}
+            stat.3 = stat.3.max(wss);
+        }
+    }
+}
+
+// window 안에서 참조된 서로 다른 페이지 수 (working set 크기)
+fn working_set(p: &Process) -> usize {
+    let mut pages: Vec<i32> = space_of(p.pid).ws_refs.iter().map(|r| r.0).collect();
+    pages.sort();
+    pages.dedup();
+    pages.len()
+}
+
+// window 안의 메모리 참조 중 페이지 폴트 비율 (page fault frequency)
+fn fault_frequency(p: &Process) -> f64 {
+    let refs = &space_of(p.pid).ws_refs;
+    if refs.is_empty() {return 0.0;}
+    refs.iter().filter(|r| r.1).count() as f64 / refs.len() as f64
+}
+
+// suspend되지 않은 모든 프로세스의 working set 크기 합
+fn working_set_total() -> usize {
+    all_processes().iter().map(|p| working_set(p)).sum()
+}
+
+// 해당 pid가 주인인 페이지를 물리메모리에서 모두 내보내기 (고정된 페이지 제외)
+fn evict_process_pages(pid: u32) {
+    unsafe {
+        for i in 0..16 {
+            if let Some(p) = PHYSICAL_MEM[i] {
+                if p.pid == pid && !page_pinned(p) {evict_frame(i as u32);}     // 고정된 페이지는 suspend되어도 물리메모리에 남음
+            }
+        }
+    }
+}
+
+// load control: working set 합이 물리메모리보다 크면(thrashing) ready queue의 process를 suspend,
+// 여유가 생기면(또는 ready queue가 비면) suspend된 process를 다시 ready queue로
+fn load_control() {
+    unsafe {
+        if WS_WINDOW == 0 {return;}
+        let mut events: Vec<String> = Vec::new();
+        if working_set_total() > 16 {
+            if !THRASHING {     // 새로운 thrashing 구간 시작
+                THRASHING = true;
+                THRASH_EPISODES.push((CYCLE, CYCLE));
+            }
+            events.push("thrashing".to_string());
+            // 페이지 폴트 빈도가 가장 높은(같으면 working set이 가장 큰) process부터 suspend, 최소 하나는 ready queue에 남김
+            while working_set_total() > 16 {
+                let mut target: Option<&Process> = None;
+                for a in RQ.iter() {
+                    if !suspendable(a.pid) || !RQ.iter().any(|q| q.pid != a.pid) {continue;}
+                    let better = match target {
+                        None => true,
+                        Some(b) => fault_frequency(a) > fault_frequency(b) || (fault_frequency(a) == fault_frequency(b) && working_set(a) > working_set(b)),
+                    };
+                    if better {target = Some(a);}
+                }
+                let pid = match target {
+                    None => break,
+                    Some(p) => p.pid,
+                };
+                suspend_process(pid);
+                events.push(format!("suspend {}", pid));
+            }
+            if working_set_total() <= 16 {      // suspend로 해결되었으면 thrashing 구간 끝
+                THRASHING = false;
+            }
+        }
+        else {
+            THRASHING = false;      // thrashing 구간 끝
+            // working set이 물리메모리에 들어갈 수 있으면 suspend된 process 재개
+            while let Some(p) = SQ.iter().find(|p| state_of(p.tid) == State::SuspendedReady) {
+                if !RQ.is_empty() && working_set_total() + working_set(p) > 16 {break;}
+                let pid = p.pid;
+                resume_process(pid);
+                events.push(format!("resume {}", pid));
+            }
+        }
+        if THRASHING {
+            THRASH_EPISODES.last_mut().unwrap().1 = CYCLE;
+        }
+        LOAD_EVENT = events.join(" ");
+    }
+}
+
+// process의 모든 thread가 ready queue, waiting queue, suspended queue 중 하나에 있는지
+// (running이거나 장치 I/O 중인 thread가 있으면 주소 공간을 함께 쓰므로 process를 suspend할 수 없음)
+fn suspendable(pid: u32) -> bool {
+    unsafe {
+        let queued = RQ.iter().chain(WQ.iter()).chain(SQ.iter()).filter(|p| p.pid == pid).count();
+        queued == threads_of(pid).len()
+    }
+}
+
+// process의 ready queue 또는 waiting queue에 있는 thread를 모두 suspended queue로 옮기고 페이지를 모두 swap out
+// ready 상태였으면 SR(suspended ready), waiting 상태였으면 SB(suspended blocked)가 됨
+fn suspend_process(pid: u32) {
+    unsafe {
+        for queue in [&mut RQ, &mut WQ] {
+            let size = queue.len();
+            for _ in 0..size {
+                let p = queue.pop_front().unwrap();
+                if p.pid != pid {
+                    queue.push_back(p);
+                    continue;
+                }
+                match state_of(p.tid) {
+                    State::Blocked(wait) => set_state(p.tid, State::SuspendedBlocked(wait)),
+                    _ => set_state(p.tid, State::SuspendedReady),
+                }
+                SQ.push_back(p);
+            }
+        }
+        evict_process_pages(pid);
+    }
+}
+
+// process의 SR 상태인 thread를 모두 ready queue로 (페이지는 접근할 때 다시 들어옴)
+fn resume_process(pid: u32) {
+    unsafe {
+        while let Some(i) = SQ.iter().position(|p| p.pid == pid && state_of(p.tid) == State::SuspendedReady) {
+            make_ready(SQ.remove(i).unwrap());
+        }
+    }
+}
+
+// 물리메모리의 빈 프레임 수
+fn free_frames() -> usize {
+    unsafe {
+        PHYSICAL_MEM.iter().filter(|p| p.is_none()).count()
+    }
+}
+
+// medium-term scheduler: 빈 프레임이 low watermark보다 적으면 정책에 따라 process 하나씩 통째로 swap out,
+// 빈 프레임이 high watermark 이상이 되면(또는 ready queue가 비면) SR 상태의 process를 swap in
+fn medium_term_schedule() {
+    unsafe {
+        if SWAP_LOW == 0 {return;}
+        let mut events: Vec<String> = Vec::new();
+        while free_frames() < SWAP_LOW {
+            // 후보: waiting queue의 모든 process, ready queue는 최소 하나를 남김
+            let mut candidates: Vec<&Process> = WQ.iter().chain(RQ.iter()).collect();
+            candidates.retain(|p| suspendable(p.pid) && (RQ.is_empty() || RQ.iter().any(|q| q.pid != p.pid)));
+            candidates.retain(|p| resident_pages(p.pid) > 0 && (SWAP_SIZE == 0 || swapped_pages(0) + resident_pages(p.pid) as usize <= SWAP_SIZE));
+            if candidates.is_empty() {break;}
+            let target = if SWAP_POLICY.contains("largest") {       // resident set이 가장 큰 process
+                candidates.iter().max_by_key(|p| (resident_pages(p.pid), p.pid)).unwrap().pid
+            } else if SWAP_POLICY.contains("priority") {        // 우선순위가 가장 낮은 process
+                candidates.iter().min_by_key(|p| (p.priority, std::cmp::Reverse(p.pid))).unwrap().pid
+            } else if SWAP_POLICY.contains("youngest") {        // 가장 최근에 생성된 process
+                candidates.iter().max_by_key(|p| p.pid).unwrap().pid
+            } else {        // blocked: waiting 중인 process 우선, 그 다음은 resident set이 큰 process
+                candidates.iter().max_by_key(|p| (matches!(state_of(p.tid), State::Blocked(_)), resident_pages(p.pid), p.pid)).unwrap().pid
+            };
+            suspend_process(target);
+            events.push(format!("swap out {}", target));
+        }
+        if (events.is_empty() && free_frames() >= SWAP_HIGH) || RQ.is_empty() {     // 방금 swap out했으면 swap in하지 않음
+            if let Some(p) = SQ.iter().find(|p| state_of(p.tid) == State::SuspendedReady) {
+                let pid = p.pid;
+                resume_process(pid);
+                events.push(format!("swap in {}", pid));
+            }
+        }
+        SWAP_EVENT = events.join(", ");
+    }
+}
+
+// 페이지가 위치한 디스크 실린더 -> 파일 페이지는 파일 영역, 나머지(익명, 공유 메모리)는 swap 영역
+fn disk_cylinder(pid: u32, page_id: i32) -> u32 {
+    unsafe {
+        if pid == 0 && FILE_PAGES.contains_key(&page_id) {
+            return DISK_CYLINDERS / 2 + ((page_id - SHARED_PAGE_BASE) as u32 * 7) % (DISK_CYLINDERS / 2);
+        }
+        (pid * 13 + page_id as u32 * 7) % (DISK_CYLINDERS / 2)
+    }
+}
+
+// 디스크 요청 출력용 이름 (예: R 1(3)@45 - 읽기, 1번 process의 3번 페이지, 45번 실린더)
+fn disk_request_name(req: &DiskRequest) -> String {
+    format!("{} {}@{}", if req.read {"R"} else {"W"}, req.label, req.cylinder)
+}
+
+// 디스크 I/O 요청을 큐에 넣음 -> 읽기 요청이면 pid의 process는 완료될 때까지 대기 (디스크 모델을 사용하지 않으면 아무것도 하지 않음)
+fn disk_submit(pid: u32, read: bool, page_pid: u32, page_id: i32) {
+    unsafe {
+        if DISK_SCHED.is_empty() {return;}
+        let label = match FILE_PAGES.get(&page_id) {
+            Some((file, block)) if page_pid == 0 => format!("{}:{}", file, block),
+            _ => format!("{}({})", if page_pid == 0 {"shm".to_string()} else {page_pid.to_string()}, page_id),
+        };
+        DISK_QUEUE.push_back(DiskRequest {pid, read, label, cylinder: disk_cylinder(page_pid, page_id), submit: CYCLE});
+        if read && pid != 0 {
+            *DISK_WAIT.entry(pid).or_insert(0) += 1;
+        }
+        if DISK_BUSY.is_none() {disk_start(CYCLE);}
+    }
+}
+
+// 디스크 스케줄링 알고리즘으로 다음 요청을 골라 start cycle부터 처리 시작 (탐색 거리 + 회전 지연만큼 걸림)
+fn disk_start(start: u32) {
+    unsafe {
+        if DISK_QUEUE.is_empty() {return;}
+        let head = DISK_HEAD;
+        let max = DISK_CYLINDERS - 1;
+        let ahead = |c: u32, up: bool| if up {c >= head} else {c <= head};
+        let nearest = |up: bool| DISK_QUEUE.iter().enumerate().filter(|(_, r)| ahead(r.cylinder, up)).min_by_key(|(_, r)| r.cylinder.abs_diff(head)).map(|(i, _)| i);
+        let mut distance = 0;       // 방향 전환 전까지 헤드가 추가로 이동하는 거리
+        let index = if DISK_SCHED.contains("fcfs") {
+            0
+        } else if DISK_SCHED.contains("sstf") {     // 헤드에서 가장 가까운 요청
+            DISK_QUEUE.iter().enumerate().min_by_key(|(_, r)| r.cylinder.abs_diff(head)).unwrap().0
+        } else if DISK_SCHED.contains("cscan") {        // 한 방향으로만 처리, 끝에 닿으면 0번 실린더로 돌아감
+            match nearest(true) {
+                Some(i) => i,
+                None => {
+                    distance = (max - head) + max;
+                    DISK_HEAD = 0;
+                    DISK_QUEUE.iter().enumerate().min_by_key(|(_, r)| r.cylinder).unwrap().0
+                }
+            }
+        } else {        // scan은 디스크 끝까지 간 뒤, look은 마지막 요청에서 바로 방향 전환
+            match nearest(DISK_UP) {
+                Some(i) => i,
+                None => {
+                    if DISK_SCHED.contains("scan") {
+                        distance = if DISK_UP {max - head} else {head};
+                        DISK_HEAD = if DISK_UP {max} else {0};
+                    }
+                    DISK_UP = !DISK_UP;
+                    let head = DISK_HEAD;
+                    DISK_QUEUE.iter().enumerate().min_by_key(|(_, r)| r.cylinder.abs_diff(head)).unwrap().0
+                }
+            }
+        };
+        let req = DISK_QUEUE.remove(index).unwrap();
+        distance += req.cylinder.abs_diff(DISK_HEAD);
+        DISK_HEAD = req.cylinder;
+        DISK_STATS.1 += distance;
+        let start = start.max(req.submit);      // 요청이 들어오기 전에는 처리할 수 없음
+        DISK_BUSY = Some((req, start + ROTATION + distance / SEEK_SPEED));
+    }
+}
+
+// 현재 cycle까지 끝난 디스크 요청을 완료 처리 -> 기다리던 process의 요청이 모두 끝나면 ready queue로 (suspend된 process는 SR로)
+fn disk_progress() {
+    unsafe {
+        while let Some((req, finish)) = DISK_BUSY.clone() {
+            if finish > CYCLE {break;}
+            DISK_BUSY = None;
+            DISK_STATS.0 += 1;
+            DISK_STATS.2 += finish - req.submit;
+            if let Some(n) = DISK_WAIT.get_mut(&req.pid) {
+                *n -= 1;
+                if *n == 0 {
+                    DISK_WAIT.remove(&req.pid);
+                    wake(req.pid, Wait::Disk);
+                }
+            }
+            disk_start(finish);     // 다음 요청은 앞 요청이 끝난 시점부터 처리
+        }
+    }
+}
+
+// running process를 ready queue로 보내거나, 디스크 읽기를 기다리는 중이면 D 상태로 waiting queue에 넣음
+fn ready_or_block() {
+    unsafe {
+        if let Some(r) = RUNNING.take() {
+            if DISK_WAIT.contains_key(&r.tid) {
+                block(r, Wait::Disk);
+            }
+            else {
+                make_ready(r);
+            }
+        }
+    }
+}
+
+// signal 번호 또는 이름(SIGUSR1 / USR1)을 signal 번호로 (지원하지 않는 signal이면 None)
+fn parse_signal(arg: &str) -> Option<u32> {
+    let name = if arg.starts_with("SIG") {arg.to_string()} else {format!("SIG{}", arg)};
+    SIGNALS.iter().find(|(n, s)| n.to_string() == arg || *s == name).map(|(n, _)| *n)
+}
+
+// signal 번호의 이름
+fn signal_name(sig: u32) -> &'static str {
+    SIGNALS.iter().find(|(n, _)| *n == sig).unwrap().1
+}
+
+// handler가 없을 때의 기본 동작 (term: 종료 / ign: 무시 / stop: 정지 / cont: 계속)
+fn default_action(sig: u32) -> &'static str {
+    match sig {
+        SIGCHLD => "ign",
+        SIGSTOP => "stop",
+        SIGCONT => "cont",
+        _ => "term",
+    }
+}
+
+// 이번 cycle의 signal 내용 추가
+fn signal_event(event: String) {
+    unsafe {
+        if !SIGNAL_EVENT.is_empty() {SIGNAL_EVENT.push_str(", ");}
+        SIGNAL_EVENT.push_str(&event);
+    }
+}
+
+// pid의 process에 signal을 보냄 -> pending에 추가 (무시되는 signal은 바로 버림), 전달은 process가 user mode로 돌아갈 때
+// SIGCONT / SIGKILL은 정지된 process를 바로 깨움 (정지된 상태로는 signal을 전달받을 수 없으므로)
+// handler가 있거나 기본 동작이 종료인 signal은 blocked 상태의 process도 기다리던 것을 그만두고 깨어나게 함
+fn send_signal(pid: u32, sig: u32) {
+    let p = match find_thread(pid) {
+        Some(p) if pid != 0 => p,
+        _ => return,
+    };
+    let handlers = &entry_of(p.pid).handlers;
+    let ignored = match handlers.get(&sig) {
+        Some(label) => label == "SIG_IGN",
+        None => default_action(sig) == "ign" || default_action(sig) == "cont",
+    };
+    if !ignored {p.pending |= 1 << sig;}
+    let interrupt = !ignored && (handlers.contains_key(&sig) || default_action(sig) == "term");
+    if sig == SIGCONT || sig == SIGKILL {
+        if sig == SIGCONT {p.pending &= !(1 << SIGSTOP);}       // 아직 전달되지 않은 SIGSTOP은 취소
+        if matches!(state_of(pid), State::Blocked(Wait::Stopped) | State::SuspendedBlocked(Wait::Stopped)) {
+            wake(pid, Wait::Stopped);
+            signal_event(format!("{} continued", pid));
+        }
+    }
+    if interrupt {interrupt_wait(pid);}
+}
+
+// blocked 상태의 thread를 기다리던 대기열에서 빼고 깨움 (정지된 thread는 SIGCONT / SIGKILL로만 깨어남)
+// mutex / semaphore / lock / 자원 / 자식 / thread를 기다리던 thread는 signal 처리 후 다시 기다리도록 명령어를 남은 명령어 앞에 다시 넣음
+fn interrupt_wait(tid: u32) {
+    unsafe {
+        let wait = match state_of(tid) {
+            State::Blocked(w) | State::SuspendedBlocked(w) => w,
+            _ => return,
+        };
+        let retry = match wait {
+            Wait::Stopped => return,
+            Wait::Disk => {
+                DISK_WAIT.remove(&tid);     // 진행 중인 읽기는 끝까지 진행되지만 더 이상 기다리지 않음
+                None
+            }
+            Wait::Sleep => {
+                TIMERS.retain(|t| t.1 != tid || t.2);       // alarm은 그대로 남김
+                None
+            }
+            Wait::Device => None,
+            Wait::Child => Some("wait".to_string()),
+            Wait::Join(t) => Some(format!("thread_join {}", t)),
+            Wait::Mutex(id) => Some(format!("mutex_lock {}", id)),
+            Wait::Sem(id) => Some(format!("sem_wait {}", id)),
+            Wait::Cond(id) => CONDS[&id].iter().find(|w| w.0 == tid).map(|w| format!("mutex_lock {}", w.1)),     // 깨어난 것으로 보고 mutex를 다시 잡음
+            Wait::Lock(id) => Some(format!("lock {}", id)),
+            Wait::Resource => RES_WAIT.iter().find(|w| w.0 == tid).map(|w| format!("request {} {}", w.1, w.2)),
+        };
+        sync_unwait(tid);
+        if wait == Wait::Resource {
+            RES_WAIT.retain(|w| w.0 != tid);
+            grant_waiters();        // 앞에서 기다리던 요청이 빠져 뒤의 요청을 들어줄 수 있음
+        }
+        if let Some(line) = retry {
+            find_thread(tid).unwrap().lines.push_front(line);
+        }
+        match io_remove(tid) {
+            Some(p) => make_ready(p),       // 장치 wait queue에서 빠짐
+            None => wake(tid, wait),
+        }
+        signal_event(format!("{} interrupted ({})", tid_label(tid), state_label(State::Blocked(wait)).trim_start_matches("blocked: ")));
+    }
+}
+
+// running process에 도착한 signal을 번호 순서대로 전달
+// handler가 있으면 handler block을 남은 명령어 앞에 넣고, 없으면 기본 동작 -> 종료 / 정지되었으면 true 반환
+fn deliver_signals() -> bool {
+    unsafe {
+        loop {
+            let r = match RUNNING.as_mut() {
+                None => return false,
+                Some(r) => r,
+            };
+            if r.pending == 0 {return false;}
+            let sig = r.pending.trailing_zeros();
+            r.pending &= !(1 << sig);
+            if let Some(label) = entry_of(r.pid).handlers.get(&sig).cloned() {
+                if label == "SIG_IGN" {continue;}
+                // label 다음 줄부터 return 전까지가 handler block
+                let start = r.code.iter().position(|l| l.trim() == format!("{}:", label)).unwrap();
+                let block: Vec<String> = r.code[start + 1..].iter().take_while(|l| l.trim() != "return").cloned().collect();
+                for line in block.into_iter().rev() {
+                    r.lines.push_front(line);
+                }
+                signal_event(format!("{} {} -> handler {}", r.pid, signal_name(sig), label));
+                continue;
+            }
+            match default_action(sig) {
+                "ign" | "cont" => continue,
+                action => {
+                    CYCLE += 1;
+                    MODE = String::from("kernel");
+                    COMMAND = String::from("signal");
+                    if action == "stop" {       // 정지: SIGCONT가 올 때까지 waiting queue에서 대기
+                        signal_event(format!("{} {}: stopped", r.pid, signal_name(sig)));
+                        block(RUNNING.take().unwrap(), Wait::Stopped);
+                        CYCLE_DONE = true;
+                        print_cycle();
+                    }
+                    else {      // 종료: exit status는 128 + signal 번호
+                        signal_event(format!("{} {}: terminated", r.pid, signal_name(sig)));
+                        terminate_running(128 + sig as i32, true);
+                        CYCLE_DONE = true;
+                        print_cycle();
+                        if NEWP.is_none() && RQ.is_empty() && WQ.is_empty() && SQ.is_empty() && IO_QUEUES.is_empty() {return true;}     // 남은 process가 없음
+                    }
+                    idle_or_schedule();
+                    return true;
+                }
+            }
+        }
+    }
+}
+
+// 명령어 kill 처리 -> pid의 process에 signal 보냄
+fn kill(pid: u32, sig: u32) {
+    unsafe {
+        // 1. 첫 번째 cycle 출력
+        CYCLE += 1;
+        COMMAND = String::from(format!("kill {pid} {}", signal_name(sig)));
+        CYCLE_DONE = true;
+        print_cycle();
+        MODE = String::from("kernel");      // 모드 스위칭
+
+        // 2. 두 번째 cycle
+        CYCLE += 1;
+        COMMAND = String::from("system call");
+        let sender = match &RUNNING {
+            None => return,
+            Some(r) => r.pid,
+        };
+        match find_thread(pid) {
+            Some(_) if pid != 0 => {
+                signal_event(format!("{} -> {} {}", sender, pid, signal_name(sig)));
+                send_signal(pid, sig);
+            }
+            _ => signal_event(format!("kill failed: no process {}", pid)),
+        }
+        make_ready(RUNNING.take().unwrap());        // running -> ready
+        CYCLE_DONE = true;
+        print_cycle();
+
+        // 3. 세 번째 cycle
+        idle_or_schedule();     // scheduling
+        return;
+    }
+}
+
+// 명령어 signal 처리 -> signal handler 등록 (label이 SIG_IGN이면 무시, SIG_DFL이면 기본 동작)
+fn signal(sig: u32, label: String) {
+    unsafe {
+        // 1. 첫 번째 cycle 출력
+        CYCLE += 1;
+        COMMAND = String::from(format!("signal {} {label}", signal_name(sig)));
+        CYCLE_DONE = true;
+        print_cycle();
+        MODE = String::from("kernel");      // 모드 스위칭
+
+        // 2. 두 번째 cycle
+        CYCLE += 1;
+        COMMAND = String::from("system call");
+        match RUNNING.as_mut() {
+            None => return,
+            Some(r) => {
+                if sig == SIGKILL || sig == SIGSTOP {       // SIGKILL / SIGSTOP은 handler 등록 불가
+                    signal_event(format!("handler failed: {} cannot be caught", signal_name(sig)));
+                }
+                else if label == "SIG_DFL" {
+                    entry_of(r.pid).handlers.remove(&sig);
+                    signal_event(format!("{} {} -> default", r.pid, signal_name(sig)));
+                }
+                else if label == "SIG_IGN" || r.code.iter().any(|l| l.trim() == format!("{}:", label)) {
+                    entry_of(r.pid).handlers.insert(sig, label.clone());
+                    signal_event(format!("{} {} -> {}", r.pid, signal_name(sig), label));
+                }
+                else {
+                    signal_event(format!("handler failed: no label {}", label));
+                }
+            }
+        }
+        make_ready(RUNNING.take().unwrap());        // running -> ready
+        CYCLE_DONE = true;
+        print_cycle();
+
+        // 3. 세 번째 cycle
+        idle_or_schedule();     // scheduling
+        return;
+    }
+}
+
+// 명령어 io 처리 -> running process를 장치의 wait queue에 넣음, 장치는 앞의 process부터 하나씩 cycle 수만큼 I/O 진행
+fn io(device: String, cycles: u32) {
+    unsafe {
+        // 1. 첫 번째 cycle 출력
+        CYCLE += 1;
+        COMMAND = String::from(format!("io {device} {cycles}"));
+        CYCLE_DONE = true;
+        print_cycle();
+        MODE = String::from("kernel");      // 모드 스위칭
+
+        // 2. 두 번째 cycle
+        CYCLE += 1;
+        COMMAND = String::from("system call");
+        match RUNNING.take() {
+            None => return,
+            Some(r) => {
+                set_state(r.tid, State::Blocked(Wait::Device));
+                let entry = IO_QUEUES.entry(device).or_insert((VecDeque::new(), 0));
+                if entry.0.is_empty() {entry.1 = CYCLE + cycles;}      // 장치가 놀고 있으면 바로 I/O 시작
+                entry.0.push_back((r, cycles));     // running -> 장치 wait queue
+            }
+        }
+        CYCLE_DONE = true;
+        print_cycle();
+
+        // 3. 세 번째 cycle
+        idle_or_schedule();     // scheduling
+        return;
+    }
+}
+
+// 명령어 sleep / gettime / alarm 처리
+// sleep: running process를 T 상태로 waiting queue에 넣고 n cycle 뒤에 깨어나는 timer 등록
+// gettime: 현재 cycle을 알려줌 / alarm: n cycle 뒤에 만료되는 alarm 등록 (이전 alarm은 취소, 0이면 취소만)
+fn timer_call(order: &str, n: u32) {
+    unsafe {
+        // 1. 첫 번째 cycle 출력
+        CYCLE += 1;
+        COMMAND = if order == "gettime" {order.to_string()} else {format!("{order} {n}")};
+        CYCLE_DONE = true;
+        print_cycle();
+        MODE = String::from("kernel");      // 모드 스위칭
+
+        // 2. 두 번째 cycle
+        CYCLE += 1;
+        COMMAND = String::from("system call");
+        match RUNNING.take() {
+            None => return,
+            Some(r) => {
+                match order {
+                    "sleep" => {
+                        TIMERS.insert((CYCLE + n, r.tid, false));
+                        block(r, Wait::Sleep);        // running -> waiting (timed wait)
+                    }
+                    "gettime" => {
+                        timer_event(format!("gettime {} -> #{}", r.pid, CYCLE));
+                        make_ready(r);
+                    }
+                    _ => {
+                        TIMERS.retain(|t| !(t.1 == r.pid && t.2));     // 이전 alarm 취소
+                        if n > 0 {TIMERS.insert((CYCLE + n, r.pid, true));}
+                        else {timer_event(format!("alarm {} cancelled", r.pid));}
+                        make_ready(r);
+                    }
+                }
+            }
+        }
+        CYCLE_DONE = true;
+        print_cycle();
+
+        // 3. 세 번째 cycle
+        idle_or_schedule();     // scheduling
+        return;
+    }
+}
+
+// 현재 cycle까지 만료된 timer 처리 -> sleep 중인 process는 ready queue로 (suspend된 process는 SR로)
+fn timer_tick() {
+    unsafe {
+        while let Some(&(at, pid, alarm)) = TIMERS.iter().next() {
+            if at > CYCLE {break;}
+            TIMERS.remove(&(at, pid, alarm));
+            if alarm {
+                timer_event(format!("alarm {}", pid));
+                send_signal(pid, SIGALRM);
+                continue;
+            }
+            wake(pid, Wait::Sleep);
+            timer_event(format!("wake {}", pid));
+        }
+    }
+}
+
+// idle 중에 무언가 일어나는 가장 가까운 cycle (만료되는 timer, 끝나는 디스크 / 장치 I/O) - timer가 없으면 다음 cycle
+fn next_event() -> u32 {
+    unsafe {
+        match TIMERS.iter().next() {
+            None => CYCLE + 1,
+            Some(t) => {
+                let mut next = t.0;
+                if let Some((_, finish)) = &DISK_BUSY {next = next.min(*finish);}
+                for (_, finish) in IO_QUEUES.values() {next = next.min(*finish + 1);}      // interrupt는 I/O가 끝난 다음 cycle에 처리
+                next.max(CYCLE + 1)
+            }
+        }
+    }
+}
+
+// 이번 cycle의 timer 내용 추가
+fn timer_event(event: String) {
+    unsafe {
+        if !TIMER_EVENT.is_empty() {TIMER_EVENT.push_str(", ");}
+        TIMER_EVENT.push_str(&event);
+    }
+}
+
+// I/O가 끝난 장치마다 interrupt cycle을 하나씩 진행 -> 맨 앞 process를 ready queue로 옮기고 다음 process의 I/O 시작
+fn handle_interrupts() {
+    unsafe {
+        loop {
+            let device = match IO_QUEUES.iter().filter(|(_, d)| d.1 <= CYCLE).min_by_key(|(_, d)| d.1) {
+                None => return,
+                Some((device, _)) => device.clone(),
+            };
+            CYCLE += 1;
+            MODE = String::from("kernel");
+            COMMAND = String::from("interrupt");
+            let entry = IO_QUEUES.get_mut(&device).unwrap();
+            let (p, _) = entry.0.pop_front().unwrap();
+            INTERRUPT_EVENT = format!("{} done, {} -> ready queue", device, thread_label(p.pid, p.tid));
+            make_ready(p);
+            match entry.0.front() {
+                None => {IO_QUEUES.remove(&device);},
+                Some(next) => entry.1 = CYCLE + next.1,
+            }
+            CYCLE_DONE = true;
+            print_cycle();
+        }
+    }
+}
+
+// 장치 wait queue에서 pid의 process를 꺼내 반환 (맨 앞이었으면 다음 process의 I/O 시작)
+fn io_remove(pid: u32) -> Option<Process> {
+    unsafe {
+        let device = IO_QUEUES.iter().find(|(_, d)| d.0.iter().any(|e| e.0.tid == pid))?.0.clone();
+        let entry = IO_QUEUES.get_mut(&device).unwrap();
+        let i = entry.0.iter().position(|e| e.0.tid == pid).unwrap();
+        let (p, _) = entry.0.remove(i).unwrap();
+        if i == 0 {
+            match entry.0.front() {
+                None => {IO_QUEUES.remove(&device);},
+                Some(next) => entry.1 = CYCLE + next.1,
+            }
+        }
+        Some(p)
+    }
+}
+
+// 백그라운드 페이지 회수 (kswapd): 빈 프레임이 low watermark보다 적으면 깨어나서
+// 페이지 교체 알고리즘으로 high watermark만큼 빈 프레임이 생길 때까지 페이지를 내보냄 -> 별도의 kernel cycle로 출력
+fn kswapd() {
+    unsafe {
+        if KSWAPD_LOW == 0 || free_frames() >= KSWAPD_LOW {return;}
+        CYCLE += 1;
+        MODE = String::from("kernel");
+        COMMAND = String::from("kswapd");
+        let before = free_frames();
+        let mut reclaimed = 0;
+        while free_frames() < KSWAPD_HIGH && !swap_full() {
+            if find_victim(&Vec::new()) == 16 {break;}      // 더 이상 회수할 페이지가 없음
+            reclaimed += 1;
+        }
+        KSWAPD_STATS.0 += 1;
+        KSWAPD_STATS.1 += reclaimed;
+        KSWAPD_EVENT = format!("reclaimed {} pages (free frames {} -> {})", reclaimed, before, free_frames());
+        CYCLE_DONE = true;
+        print_cycle();
+    }
+}
+
+// 옵션에 따라 모든 cycle이 끝난 뒤 요약 정보 CYCLE_INFO에 추가하는 함수
+fn print_summary() {
+    unsafe {
+        let mut temp = String::new();
+        if WS_WINDOW > 0 {
+            temp.push_str(&format!("thrashing episodes: {}\n", THRASH_EPISODES.len()));
+            for (start, end) in &THRASH_EPISODES {
+                temp.push_str(&format!("  cycle #{} ~ #{}\n", start, end));
+            }
+            for (pid, (name, refs, faults, max_ws)) in &WS_STATS {
+                temp.push_str(&format!("{}({}): references {}, faults {}, max working set {}\n", pid, name, refs, faults, max_ws));
+            }
+        }
+        if KSWAPD_LOW > 0 {
+            temp.push_str(&format!("kswapd: wakeups {}, pages reclaimed {}\n", KSWAPD_STATS.0, KSWAPD_STATS.1));
+        }
+        if SWAP_SIZE > 0 {
+            temp.push_str(&format!("oom kills: {}\n", OOM_KILLS.len()));
+            for kill in &OOM_KILLS {
+                temp.push_str(&format!("  {}\n", kill));
+            }
+        }
+        if !DISK_SCHED.is_empty() {
+            let (done, seek, latency) = DISK_STATS;
+            temp.push_str(&format!("disk ({}): requests {}, total seek {}, average latency {:.2}\n", DISK_SCHED, done, seek, latency as f64 / done.max(1) as f64));
+        }
+        if !FILE_PAGES.is_empty() {
+            temp.push_str(&format!("page cache: hits {}, misses {}, write-backs {}\n", FILE_STATS.0, FILE_STATS.1, FILE_STATS.2));
+        }
+        if !RESOURCES.is_empty() || !DEADLOCKS.is_empty() {
+            temp.push_str(&format!("deadlocks: {}\n", DEADLOCKS.len()));
+            for deadlock in &DEADLOCKS {
+                temp.push_str(&format!("  {}\n", deadlock));
+            }
+        }
+        if PSTREE {
+            // 회수된 process는 exit status로, 아직 process table에 남은 process는 마지막 상태로 (생성된 모든 process)
+            let mut nodes = HISTORY.clone();
+            for (&pid, e) in PROCS.iter() {
+                if e.state == State::Terminated {continue;}
+                nodes.insert(pid, (e.ppid, format!("{}({}, {})", thread_label(e.tgid, pid), e.name, state_label(e.state))));
+            }
+            temp.push_str(&format!("process tree:\n{}", if nodes.is_empty() {"none\n".to_string()} else {draw_tree(&nodes)}));
+        }
+        if temp.is_empty() {return;}
+        CYCLE_INFO.push_str(&format!("[summary]\n{}", temp));
+    }
+}
+
+// 테이블 노드 번호를 받고 출력용 이름 반환 (D: 최상위 디렉터리, M: 중간 디렉터리, T: 말단 테이블)
+fn pt_node_name(node: usize) -> String {
+    if node == 0 {return "D".to_string();}
+    if node < 3 {return format!("M{}", node-1);}
+    format!("T{}", node-3)
+}
+
+// 테이블 노드가 담당하는 가상 메모리 index 범위 [start, end) 반환
+fn pt_node_range(node: usize) -> (usize, usize) {
+    if node == 0 {return (0, 32);}
+    if node < 3 {return ((node-1)*16, node*16);}
+    ((node-3)*4, (node-2)*4)
+}
+
+// 가상 메모리 index를 변환할 때 거치는 테이블 노드들을 위에서부터 순서대로 반환
+fn pt_path(v_index: usize) -> Vec<usize> {
+    unsafe {
+        let mut path: Vec<usize> = vec![0];
+        if PT_LEVEL == 3 {path.push(1 + v_index/16);}
+        path.push(3 + v_index/4);
+        path
+    }
+}
+
+// 테이블 노드가 존재하는지 확인 -> 담당 범위에 할당된 페이지가 하나라도 있어야 테이블 페이지가 존재함
+fn pt_node_exists(p: &Process, node: usize) -> bool {
+    unsafe {
+        if PT_LEVEL == 2 && (node == 1 || node == 2) {return false;}
+    }
+    let (start, end) = pt_node_range(node);
+    for i in start..end {
+        if !space_of(p.pid).virtual_mem[i].is_none() {return true;}
+    }
+    false
+}
+
+// running process에서 가상 메모리 index까지의 경로에 있는 테이블 페이지가 모두 물리메모리에 있는지 확인
+fn pt_resident(v_index: usize) -> bool {
+    unsafe {
+        if PT_LEVEL < 2 {return true;}
+        match &RUNNING {
+            None => true,
+            Some(r) => {
+                for node in pt_path(v_index) {
+                    if space_of(r.pid).table_frame[node] == -1 {return false;}
+                }
+                true
+            }
+        }
+    }
+}
+
+// 경로에 있는 테이블 페이지들의 참조 카운트 +1 (LRU일 경우 PQ 업데이트)
+fn pt_touch(v_index: usize) {
+    unsafe {
+        if PT_LEVEL < 2 {return;}
+        let mut frames: Vec<i32> = Vec::new();
+        match &RUNNING {
+            None => return,
+            Some(r) => {
+                for node in pt_path(v_index) {
+                    frames.push(space_of(r.pid).table_frame[node]);
+                }
+            }
+        }
+        for f in frames {
+            if f == -1 {continue;}
+            p_mem_count_plus(f as u32);
+            if CHANGE_ALGORITHM.contains("lru") {
+                lru_update(PHYSICAL_MEM[f as usize].unwrap());
+            }
+        }
+    }
+}
+
+// 경로에 있는 테이블 페이지 중 물리메모리에 없는 것들을 새로 할당 (테이블 페이지 폴트 처리)
+fn pt_load(v_index: usize) -> bool {
+    unsafe {
+        if PT_LEVEL < 2 {return true;}
+        for node in pt_path(v_index) {
+            let pid = match &RUNNING {
+                None => return false,
+                Some(r) => {
+                    if space_of(r.pid).table_frame[node] != -1 {      // 이미 물리메모리에 있는 테이블 페이지
+                        continue;
+                    }
+                    r.pid
+                }
+            };
+            let p_index = match find_physical_space(1) {      // 테이블 페이지가 들어갈 물리메모리 index
+                None => return false,      // 공간을 찾는 도중 OOM killer에 의해 종료됨
+                Some(mut v) => v.pop().unwrap(),
+            };
+            let new_page = create_page(pid, node as i32, -1, PERM_R | PERM_W, false, 1, node as i32);
+            PHYSICAL_MEM[p_index as usize] = Some(new_page);
+            if CHANGE_ALGORITHM.contains("fifo") || CHANGE_ALGORITHM.contains("lru") {
+                PQ.push_back(new_page);
+            }
+            match &RUNNING {
+                None => return false,
+                Some(r) => space_of(r.pid).table_frame[node] = p_index as i32,
+            }
+        }
+        true
+    }
+}
+
+// 담당 범위에 페이지가 하나도 남지 않은 테이블 페이지를 물리메모리에서 해제
+fn pt_free_unused(c: &Process) {
+    unsafe {
+        if PT_LEVEL < 2 {return;}
+        for node in 0..PT_NODES {
+            if space_of(c.pid).table_frame[node] == -1 || pt_node_exists(c, node) {continue;}
+            let p_index = space_of(c.pid).table_frame[node] as usize;
+            if CHANGE_ALGORITHM.contains("fifo") || CHANGE_ALGORITHM.contains("lru") {
+                remove_PQ(PHYSICAL_MEM[p_index].unwrap());
+            }
+            PHYSICAL_MEM[p_index] = None;
+            space_of(c.pid).table_frame[node] = -1;
+        }
+    }
+}
+
+// victim으로 선택된 테이블 페이지의 주인 프로세스에서 해당 테이블 노드가 물리메모리를 가리키지 않게 하는 함수
+fn pt_victim_update(victim: Page) {
+    unsafe {
+        if let Some(s) = SPACES.get_mut(&victim.pid) {
+            s.table_frame[victim.table as usize] = -1;
+        }
+    }
+}
+
+// running process의 페이지 테이블 계층 구조를 출력용 문자열로 반환
+fn pt_hierarchy(p: &Process) -> String {
+    let mut result = String::new();
+    let mut total = 0;
+    let mut resident = 0;
+    for node in 0..PT_NODES {
+        if !pt_node_exists(p, node) {continue;}
+        total += 1;
+        let frame = if space_of(p.pid).table_frame[node] == -1 {"-".to_string()} else {resident += 1; space_of(p.pid).table_frame[node].to_string()};
+        let depth = if node == 0 {0} else if node < 3 {1} else {pt_path(pt_node_range(node).0).len() - 1};
+        let mut line = format!("{}{}[{}]", "  ".repeat(depth), pt_node_name(node), frame);
+        if node >= 3 {      // 말단 테이블은 담당하는 페이지들의 프레임 번호도 출력
+            let (start, end) = pt_node_range(node);
+            let mut entries: Vec<String> = Vec::new();
+            for i in start..end {
+                if space_of(p.pid).page_table[i][1] == -1 {entries.push("-".to_string());}
+                else {entries.push(space_of(p.pid).page_table[i][1].to_string());}
+            }
+            line.push_str(&format!(" |{}|", entries.join(" ")));
+        }
+        result.push_str(&format!("{}\n", line));
+    }
+    format!("page table hierarchy: (table pages in memory: {}/{})\n{}", resident, total, result)
+}
+
+// 명령어 fork_and_exec 처리 
+fn fork_and_exec(name: String) {
+    unsafe{
+        // 1. fork 명령어가 실행된 첫 번째 cycle 출력 
+        CYCLE += 1;
+        COMMAND = String::from(format!("fork_and_exec {name}"));
+        CYCLE_DONE = true;
+        print_cycle();
+        MODE = String::from("kernel");
+        
+        // 2. 두 번째 cycle 출력
+        CYCLE += 1;
+        COMMAND = String::from("system call");
+        match &RUNNING {
+            None => return,
+            Some(r) => {
+                // 새로운 process 생성
+                    // 새로 들어온 process를 읽고 한 줄씩 VecDeque에 저장
+                let process_dir: String = format!("{}\\{}", INPUT_DIR, name).to_string();       
+                let mut lines: VecDeque<String> = VecDeque::new();
+                let file = File::open(process_dir).unwrap();
+                let reader = BufReader::new(file).lines();
+                for line in reader {
+                    lines.push_back(line.unwrap());
+                }
+                if !admit_claims(&name, &lines) {       // claim을 끝까지 들어줄 수 없는 process는 만들지 않음
+                    make_ready(r.clone());
+                    RUNNING = None;
+                }
+                else {
+                    PID += 1;
+                    let new_r = r.clone();
+                    let s = space_of(new_r.pid);
+                    // running process의 페이지 모두 CoW 공유 상태로 (물리메모리도 수정)
+                    for i in 0..32 {
+                        if !s.virtual_mem[i].is_none() {
+                            if s.virtual_mem[i].unwrap().pid == 0 {continue;}      // 공유 메모리 페이지는 CoW 없이 계속 공유
+                            s.virtual_mem[i] = Some(cow_change(s.virtual_mem[i].unwrap(), true));     // CoW로 변경
+                            let frame = frame_of(&new_r, i);
+                            if frame != -1 {
+                                let old_page = PHYSICAL_MEM[frame as usize].unwrap();
+                                PHYSICAL_MEM[frame as usize] = Some(cow_change(old_page, true));
+                            }
+                        }
+                        else {continue;}
+                    }
+                    // 부모 프로세스의 가상 메모리를 CoW
+                    let p = create_process(name, PID, new_r.pid, lines, s.virtual_mem, s.page_table, s.page_id, s.allocation_id);
+                    for i in 0..32 {
+                        if let Some(page) = space_of(p.pid).virtual_mem[i] {
+                            rmap_add(page, p.pid, i);       // 자식 프로세스도 부모의 페이지들을 매핑
+                        }
+                    }
+                    entry_of(p.pid).fds = entry_of(new_r.pid).fds.clone();      // 파일 디스크립터 테이블 상속 (열린 파일의 offset은 부모와 공유)
+                    for i in entry_of(p.pid).fds.values() {
+                        OPEN_FILES.get_mut(i).unwrap().3 += 1;
+                    }
+                    for (_, seg) in SHM.iter_mut() {        // 부모가 attach한 공유 메모리 segment는 자식도 attach
+                        if seg.0.iter().any(|id| space_of(p.pid).virtual_mem.iter().any(|v| matches!(v, Some(q) if q.pid == 0 && q.page_id == *id))) {
+                            seg.1 += 1;
+                        }
+                    }
+                    NEWP = Some(p);     // new process 갱신
+                    make_ready(new_r.clone());      // 부모 process(현재 running process) ready queue에 넣기
+                    RUNNING = None;
+                }
+            }
+        }
+        CYCLE_DONE = true;
+        print_cycle();
+
+        // 3. 세 번째 cycle 출력
+            // new 상태의 process ready queue에 넣기 (claim 때문에 거부되었으면 없음)
+        if let Some(p) = NEWP.take() {
+            make_ready(p);
+        }
+        idle_or_schedule();     // scheduling
+        return;
+    }
+}
+
+// process table에서 pid의 상태 (table에 없으면 terminated)
+fn state_of(pid: u32) -> State {
+    unsafe {
+        match PROCS.get(&pid) {
+            None => State::Terminated,
+            Some(e) => e.state,
+        }
+    }
+}
+
+// process 상태 전이 - 허용되지 않는 전이는 시뮬레이터 버그이므로 바로 중단
+fn set_state(pid: u32, to: State) {
+    unsafe {
+        let from = state_of(pid);
+        let valid = match (from, to) {
+            (State::New, State::Ready) => true,
+            (State::Ready, State::Running) | (State::Ready, State::SuspendedReady) => true,
+            (State::Running, State::Ready) | (State::Running, State::Blocked(_)) => true,
+            (State::Blocked(_), State::Ready) => true,
+            (State::Blocked(Wait::Cond(_)), State::Blocked(Wait::Mutex(_))) => true,        // cond_signal로 깨어났지만 mutex를 다시 기다림
+            (State::SuspendedBlocked(Wait::Cond(_)), State::SuspendedBlocked(Wait::Mutex(_))) => true,
+            (State::Blocked(a), State::SuspendedBlocked(b)) => a == b,
+            (State::SuspendedBlocked(_), State::SuspendedReady) => true,
+            (State::SuspendedReady, State::Ready) => true,
+            (State::Zombie(_), State::Terminated) => true,
+            (State::Zombie(_), _) | (State::Terminated, _) => false,
+            (_, State::Zombie(_)) => true,      // exit 또는 kill로 종료
+            _ => false,
+        };
+        if !valid {
+            panic!("invalid state transition: pid {} {:?} -> {:?}", pid, from, to);
+        }
+        PROCS.get_mut(&pid).unwrap().state = to;
+    }
+}
+
+// trace에 출력할 상태 이름
+fn state_label(state: State) -> String {
+    let wait = |w: Wait| match w {
+        Wait::Child => "child".to_string(),
+        Wait::Disk => "disk".to_string(),
+        Wait::Device => "io".to_string(),
+        Wait::Sleep => "sleep".to_string(),
+        Wait::Stopped => "stopped".to_string(),
+        Wait::Join(tid) => format!("join {}", tid),
+        Wait::Mutex(id) => format!("mutex {}", id),
+        Wait::Sem(id) => format!("sem {}", id),
+        Wait::Cond(id) => format!("cond {}", id),
+        Wait::Resource => "resource".to_string(),
+        Wait::Lock(id) => format!("lock {}", id),
+    };
+    match state {
+        State::New => "new".to_string(),
+        State::Ready => "ready".to_string(),
+        State::Running => "running".to_string(),
+        State::Blocked(Wait::Stopped) => "stopped".to_string(),
+        State::Blocked(w) => format!("blocked: {}", wait(w)),
+        State::SuspendedReady => "SR".to_string(),
+        State::SuspendedBlocked(_) => "SB".to_string(),
+        State::Zombie(code) => format!("zombie: {}", code),
+        State::Terminated => "terminated".to_string(),
+    }
+}
+
+// process를 ready 상태로 ready queue에 넣음
+fn make_ready(p: Process) {
+    unsafe {
+        set_state(p.tid, State::Ready);
+        RQ.push_back(p);
+    }
+}
+
+// process를 blocked 상태로 waiting queue에 넣음
+fn block(p: Process, wait: Wait) {
+    unsafe {
+        set_state(p.tid, State::Blocked(wait));
+        WQ.push_back(p);
+    }
+}
+
+// wait 이벤트를 기다리던 process 깨우기 -> waiting queue에 있으면 ready queue로, suspend된 process는 SB -> SR
+fn wake(pid: u32, wait: Wait) {
+    unsafe {
+        if state_of(pid) == State::Blocked(wait) {
+            if let Some(i) = WQ.iter().position(|p| p.tid == pid) {
+                make_ready(WQ.remove(i).unwrap());
+            }
+        }
+        else if state_of(pid) == State::SuspendedBlocked(wait) {
+            set_state(pid, State::SuspendedReady);
+        }
+    }
+}
+
+// zombie process의 exit status 회수 -> terminated (이번 cycle 출력 후 process table에서 제거)
+fn reap(pid: u32) {
+    unsafe {
+        if let State::Zombie(code) = state_of(pid) {
+            let e = &PROCS[&pid];
+            if !REAP_EVENT.is_empty() {REAP_EVENT.push_str(", ");}
+            REAP_EVENT.push_str(&format!("{}({}) exit {}", thread_label(e.tgid, pid), e.name, code));
+            if e.ppid == 0 {REAP_EVENT.push_str(" (no parent)");}       // init처럼 부모가 없는 process는 회수한 process가 없음
+            HISTORY.insert(pid, (e.ppid, format!("{}({}, exit {})", thread_label(e.tgid, pid), e.name, code)));
+            set_state(pid, State::Terminated);
+        }
+    }
+}
+
+// process table을 부모-자식 관계의 tree로 출력 (부모가 table에 없는 process가 root)
+// 한 줄에 process 하나씩 pid(이름, 상태, 물리메모리에 있는 페이지 수)
+fn pstree() -> String {
+    unsafe {
+        let nodes: BTreeMap<u32, (u32, String)> = PROCS.iter().map(|(&pid, e)| {
+            (pid, (e.ppid, format!("{}({}, {}, {} pages)", thread_label(e.tgid, pid), e.name, state_label(e.state), resident_pages(e.tgid))))
+        }).collect();
+        draw_tree(&nodes)
+    }
+}
+
+// tid -> (parent ID, 출력할 내용)을 부모-자식 관계의 tree로 그림 (부모가 없는 node가 root)
+fn draw_tree(nodes: &BTreeMap<u32, (u32, String)>) -> String {
+    fn draw(nodes: &BTreeMap<u32, (u32, String)>, pid: u32, prefix: &str, last: bool, root: bool, out: &mut String) {
+        let branch = if root {""} else if last {"`- "} else {"|- "};
+        out.push_str(&format!("{}{}{}\n", prefix, branch, nodes[&pid].1));
+        let children: Vec<u32> = nodes.iter().filter(|(_, n)| n.0 == pid).map(|(c, _)| *c).collect();
+        let prefix = if root {prefix.to_string()} else if last {format!("{}   ", prefix)} else {format!("{}|  ", prefix)};
+        for (i, &c) in children.iter().enumerate() {
+            draw(nodes, c, &prefix, i + 1 == children.len(), false, out);
+        }
+    }
+    let mut out = String::new();
+    for (&pid, n) in nodes.iter() {
+        if !nodes.contains_key(&n.0) {draw(nodes, pid, "", true, true, &mut out);}
+    }
+    out
+}
+
+// 부모가 종료된 process를 init(pid 1)의 자식으로 입양
+fn reparent(pid: u32) {
+    unsafe {
+        let e = PROCS.get_mut(&pid).unwrap();
+        e.ppid = 1;
+        e.orphan = true;
+        if let Some(p) = find_thread(pid) {p.ppid = 1;}        // thread들의 부모는 속한 process 그대로
+        if !REPARENT_EVENT.is_empty() {REPARENT_EVENT.push_str(", ");}
+        REPARENT_EVENT.push_str(&format!("{}({}) -> 1(init)", pid, e.name));
+        reap(pid);      // 이미 zombie면 init이 바로 회수
+    }
+}
+
+// 명령어 wait 처리
+fn wait() {
+    unsafe{
+        // 1. 첫 번째 cycle 출력
+        CYCLE += 1;
+        COMMAND = String::from("wait");
+        CYCLE_DONE = true;
+        print_cycle();
+        MODE = String::from("kernel");      // 모드 스위칭
+
+        // 2. 두 번째 cycle 출력
+        CYCLE += 1;
+        COMMAND = String::from("system call");
+            // process table에서 zombie 자식 또는 아직 실행 중인 자식이 있는지 확인
+        match &RUNNING {
+            None => return,
+            Some(p) => {
+                let zombie = PROCS.iter().find(|(&pid, e)| e.ppid == p.pid && e.tgid == pid && matches!(e.state, State::Zombie(_))).map(|(pid, _)| *pid);
+                let alive = PROCS.iter().any(|(&pid, e)| e.ppid == p.pid && e.tgid == pid && !matches!(e.state, State::Zombie(_) | State::Terminated));     // thread는 wait 대상이 아님
+                match zombie {
+                    Some(z) => {        // 이미 종료된 자식의 exit status 회수 -> 기다리지 않음
+                        reap(z);
+                        make_ready(p.clone());
+                    }
+                    None if alive => block(p.clone(), Wait::Child),     // 자식이 종료될 때까지 waiting
+                    None => make_ready(p.clone()),      // 자식 프로세스 없음
+                }
+                RUNNING = None;
+                CYCLE_DONE = true;
+                print_cycle();
+            }
+        }
+
+        // 3. 세 번째 cycle 출력 
+        idle_or_schedule();
+    }
+}
+
+// 명령어 exit 처리 (exit status는 부모가 wait로 회수할 때까지 process table에 남음)
+fn exit(code: i32) {
+    unsafe{
+        // 1. 첫 번째 cycle 출력
+        CYCLE += 1;
+        COMMAND = if code == 0 {String::from("exit")} else {format!("exit {code}")};
+        CYCLE_DONE = true;
+        print_cycle();
+        MODE = String::from("kernel");      // 모드 스위칭
+
+        // 2. 두 번째 cycle 출력
+        CYCLE += 1;
+        COMMAND = String::from("system call");
+        if RUNNING.is_none() {return;}
+        terminate_running(code, false);
+        CYCLE_DONE = true;
+        print_cycle();
+
+        // 3. 세 번째 cycle 출력
+        match &NEWP {
+            Some(_) => {            // 종료되지 않은 new process가 존재할 경우
+                idle_or_schedule();      
+            },
+            None => {
+                if RQ.is_empty() && WQ.is_empty() && SQ.is_empty() && IO_QUEUES.is_empty() {     // 종료되지 않은 프로세스가 running process 단 하나일 경우
+                    return;
+                } else {        // 종료되지 않은 프로세스가 더 남아있는 경우
+                    idle_or_schedule();
+                }
+            }
+        }
+    }
+}
+
+// running process 종료 처리: zombie 상태로 & 기다리던 부모 깨우기 & 모든 메모리 해제
+// thread가 exit하면 join하던 thread만 깨우고 주소 공간은 그대로, main thread가 종료되거나 group이면(signal / fault로 종료) process의 모든 thread가 함께 종료
+fn terminate_running(code: i32, group: bool) {
+    unsafe{
+        match &RUNNING {
+            None => return,
+            Some(c) if c.tid != c.pid && !group => {
+                set_state(c.tid, State::Zombie(code));
+                sync_cleanup(c.tid);
+                resource_cleanup(c.tid);
+                let joiner = PROCS.iter().find(|(_, e)| matches!(e.state, State::Blocked(Wait::Join(t)) | State::SuspendedBlocked(Wait::Join(t)) if t == c.tid)).map(|(tid, _)| *tid);
+                if let Some(j) = joiner {
+                    wake(j, Wait::Join(c.tid));
+                    reap(c.tid);
+                }
+                RUNNING = None;
+            }
+            Some(c) => {
+                if c.tid != c.pid {unqueue(c.pid);}     // thread가 process 전체를 종료시키면 main thread도 queue에서 빼냄
+                kill_threads(c.pid, code);
+                set_state(c.pid, State::Zombie(code));
+                sync_cleanup(c.pid);
+                resource_cleanup(c.pid);
+                let ppid = PROCS[&c.pid].ppid;
+                send_signal(ppid, SIGCHLD);       // 부모에게 자식의 종료 알림
+                // 부모 process가 waiting 중이면 깨우고 바로 exit status 회수 (swap out된 부모는 SB -> SR)
+                match state_of(ppid) {
+                    State::Blocked(Wait::Child) | State::SuspendedBlocked(Wait::Child) => {
+                        wake(ppid, Wait::Child);
+                        reap(c.pid);
+                    }
+                    State::Zombie(_) | State::Terminated => reap(c.pid),       // 회수할 부모가 없음
+                    _ if PROCS[&c.pid].orphan => reap(c.pid),       // init이 입양한 orphan은 init이 바로 회수
+                    _ => {}
+                }
+                    // 해당 프로세스의 모든 allocation id에 대해 release
+                let mut al:Vec<u32> = Vec::new();
+                for i in 0..32 {
+                    if !space_of(c.pid).virtual_mem[i].is_none() {
+                        let a = space_of(c.pid).virtual_mem[i].unwrap().allocation_id as u32;
+                        if !al.contains(&a) {al.push(a);}
+                    }
+                }
+                let size = al.len();
+                for _ in 0..size {
+                    release(al.pop().unwrap());
+                }
+                SPACES.remove(&c.pid);      // 주소 공간 제거 (thread들은 이미 모두 종료됨)
+                for &i in entry_of(c.pid).fds.values() {      // 열린 파일 모두 닫기
+                    fs_close(i);
+                }
+                entry_of(c.pid).fds.clear();
+                TIMERS.retain(|t| t.1 != c.pid);        // 등록한 alarm 취소
+                // 자식 process는 orphan이 되어 init에 입양됨 (zombie 자식은 init이 바로 회수)
+                let children: Vec<u32> = PROCS.iter().filter(|(_, e)| e.ppid == c.pid && e.state != State::Terminated).map(|(pid, _)| *pid).collect();
+                let init_alive = !matches!(state_of(1), State::Zombie(_) | State::Terminated);
+                for child in children {
+                    if c.pid != 1 && init_alive {reparent(child);}
+                    else {reap(child);}     // init이 없으면 zombie 자식은 회수할 부모가 없으므로 바로 회수
+                }
+                RUNNING = None;
+            }
+        }
+    }
+}
+
+// thread를 new / ready / waiting / suspended / 장치 queue에서 빼고 기다리던 디스크 읽기와 alarm 취소
+fn unqueue(tid: u32) {
+    unsafe {
+        if NEWP.as_ref().map_or(false, |n| n.tid == tid) {NEWP = None;}
+        RQ.retain(|p| p.tid != tid);
+        WQ.retain(|p| p.tid != tid);
+        SQ.retain(|p| p.tid != tid);
+        io_remove(tid);
+        DISK_WAIT.remove(&tid);
+        TIMERS.retain(|t| t.1 != tid);
+    }
+}
+
+// process 전체가 종료될 때 같은 process의 나머지 thread 종료 -> 각 queue에서 빼고 바로 회수 (주소 공간은 main thread 몫으로 해제)
+fn kill_threads(pid: u32, code: i32) {
+    unsafe {
+        let tids: Vec<u32> = PROCS.iter().filter(|(&tid, e)| e.tgid == pid && tid != pid).map(|(tid, _)| *tid).collect();
+        for tid in tids {
+            unqueue(tid);
+            if !matches!(state_of(tid), State::Zombie(_) | State::Terminated) {set_state(tid, State::Zombie(code));}
+            sync_cleanup(tid);
+            resource_cleanup(tid);
+            reap(tid);
+        }
+    }
+}
+
+// 명령어 thread_create 처리 -> label부터 return까지를 실행하는 thread 생성 (running process와 주소 공간을 공유)
+fn thread_create(label: String) {
+    unsafe{
+        // 1. 첫 번째 cycle 출력
+        CYCLE += 1;
+        COMMAND = String::from(format!("thread_create {label}"));
+        CYCLE_DONE = true;
+        print_cycle();
+        MODE = String::from("kernel");      // 모드 스위칭
+
+        // 2. 두 번째 cycle 출력
+        CYCLE += 1;
+        COMMAND = String::from("system call");
+        match &RUNNING {
+            None => return,
+            Some(r) => {
+                match r.code.iter().position(|l| l.trim() == format!("{}:", label)) {
+                    None => thread_event(format!("thread_create failed: no label {}", label)),
+                    Some(start) => {
+                        // label 다음 줄부터 return 전까지 실행하고 종료
+                        let mut lines: VecDeque<String> = r.code[start + 1..].iter().take_while(|l| l.trim() != "return").cloned().collect();
+                        lines.push_back(String::from("exit"));
+                        PID += 1;
+                        let mut t = r.clone();
+                        t.tid = PID;
+                        t.ppid = r.pid;     // thread를 만든 process가 부모 (process table 항목과 같음)
+                        t.lines = lines;
+                        t.pending = 0;
+                        PROCS.insert(PID, ProcEntry {name: t.name.clone(), ppid: r.pid, state: State::New, tgid: r.pid, orphan: false, fds: BTreeMap::new(), handlers: BTreeMap::new()});
+                        register_claims(PID, &t.code);
+                        thread_event(format!("{} created {} ({})", thread_label(r.pid, r.tid), thread_label(t.pid, t.tid), label));
+                        NEWP = Some(t);     // new 상태의 thread
+                    }
+                }
+                make_ready(r.clone());      // running -> ready
+                RUNNING = None;
+            }
+        }
+        CYCLE_DONE = true;
+        print_cycle();
+
+        // 3. 세 번째 cycle 출력
+            // new 상태의 thread ready queue에 넣기
+        if let Some(p) = NEWP.take() {make_ready(p);}
+        idle_or_schedule();     // scheduling
+        return;
+    }
+}
+
+// 명령어 thread_join 처리 -> 같은 process의 thread가 종료될 때까지 기다리고 회수
+fn thread_join(tid: u32) {
+    unsafe{
+        // 1. 첫 번째 cycle 출력
+        CYCLE += 1;
+        COMMAND = String::from(format!("thread_join {tid}"));
+        CYCLE_DONE = true;
+        print_cycle();
+        MODE = String::from("kernel");      // 모드 스위칭
+
+        // 2. 두 번째 cycle 출력
+        CYCLE += 1;
+        COMMAND = String::from("system call");
+        match RUNNING.take() {
+            None => return,
+            Some(r) => {
+                let target = PROCS.get(&tid).filter(|e| e.tgid == r.pid && tid != r.pid && tid != r.tid).map(|e| e.state);
+                match target {
+                    Some(State::Zombie(_)) => {     // 이미 종료된 thread -> 바로 회수
+                        reap(tid);
+                        make_ready(r);
+                    }
+                    Some(State::Terminated) | None => {
+                        thread_event(format!("thread_join failed: no thread {}", tid));
+                        make_ready(r);
+                    }
+                    Some(_) => block(r, Wait::Join(tid)),       // thread가 종료될 때까지 waiting
+                }
+            }
+        }
+        CYCLE_DONE = true;
+        print_cycle();
+
+        // 3. 세 번째 cycle 출력
+        idle_or_schedule();     // scheduling
+        return;
+    }
+}
+
+// 이번 cycle의 thread 생성 / join 결과 추가
+fn thread_event(event: String) {
+    unsafe {
+        if !THREAD_EVENT.is_empty() {THREAD_EVENT.push_str(", ");}
+        THREAD_EVENT.push_str(&event);
+    }
+}
+
+// 동기화 명령어 처리 (mutex_lock / mutex_unlock, sem_init / sem_wait / sem_post, cond_wait / cond_signal, lock / unlock)
+// 객체는 id로 구분되고 처음 사용할 때 만들어짐 (mutex는 풀린 상태, semaphore는 0) - 모든 process와 thread가 공유
+fn sync_call(order: &str, args: Vec<u32>) {
+    unsafe {
+        // 1. 첫 번째 cycle 출력
+        CYCLE += 1;
+        COMMAND = format!("{order} {}", args.iter().map(|a| a.to_string()).collect::<Vec<String>>().join(" "));
+        CYCLE_DONE = true;
+        print_cycle();
+        MODE = String::from("kernel");      // 모드 스위칭
+
+        // 2. 두 번째 cycle
+        CYCLE += 1;
+        COMMAND = String::from("system call");
+        match RUNNING.take() {
+            None => return,
+            Some(r) => {
+                let id = args[0];
+                let me = thread_label(r.pid, r.tid);
+                match order {
+                    "mutex_lock" => {
+                        let m = MUTEXES.entry(id).or_insert((None, VecDeque::new()));
+                        match m.0 {
+                            None => {
+                                m.0 = Some(r.tid);
+                                make_ready(r);
+                            }
+                            Some(owner) if owner == r.tid => {
+                                sync_event(format!("mutex_lock failed: {} already holds mutex {}", me, id));
+                                make_ready(r);
+                            }
+                            Some(_) => {        // 다른 thread가 가지고 있으면 mutex의 wait queue에서 기다림
+                                m.1.push_back(r.tid);
+                                block(r, Wait::Mutex(id));
+                            }
+                        }
+                    }
+                    "mutex_unlock" => {
+                        if MUTEXES.get(&id).map_or(false, |m| m.0 == Some(r.tid)) {mutex_release(id);}
+                        else {sync_event(format!("mutex_unlock failed: {} does not hold mutex {}", me, id));}
+                        make_ready(r);
+                    }
+                    "lock" => {
+                        let l = LOCKS.entry(id).or_insert((None, VecDeque::new()));
+                        match l.0 {
+                            None => {
+                                l.0 = Some(r.tid);
+                                make_ready(r);
+                            }
+                            Some(owner) if owner == r.tid => {
+                                sync_event(format!("lock failed: {} already holds lock {}", me, id));
+                                make_ready(r);
+                            }
+                            Some(_) => {        // 다른 thread가 가지고 있으면 기다림 (inherit면 가진 thread가 우선순위를 물려받음)
+                                l.1.push_back(r.tid);
+                                block(r, Wait::Lock(id));
+                            }
+                        }
+                    }
+                    "unlock" => {
+                        if LOCKS.get(&id).map_or(false, |l| l.0 == Some(r.tid)) {lock_release(id);}
+                        else {sync_event(format!("unlock failed: {} does not hold lock {}", me, id));}
+                        make_ready(r);
+                    }
+                    "sem_init" => {
+                        SEMAPHORES.entry(id).or_insert((0, VecDeque::new())).0 = args[1];
+                        make_ready(r);
+                    }
+                    "sem_wait" => {
+                        let sem = SEMAPHORES.entry(id).or_insert((0, VecDeque::new()));
+                        if sem.0 > 0 {
+                            sem.0 -= 1;
+                            make_ready(r);
+                        }
+                        else {      // 값이 0이면 sem_post가 올 때까지 기다림
+                            sem.1.push_back(r.tid);
+                            block(r, Wait::Sem(id));
+                        }
+                    }
+                    "sem_post" => {
+                        let sem = SEMAPHORES.entry(id).or_insert((0, VecDeque::new()));
+                        match sem.1.pop_front() {
+                            None => sem.0 += 1,
+                            Some(t) => {        // 기다리는 thread가 있으면 값을 올리지 않고 바로 넘겨줌
+                                sync_event(format!("sem {} -> {}", id, tid_label(t)));
+                                wake(t, Wait::Sem(id));
+                            }
+                        }
+                        make_ready(r);
+                    }
+                    "cond_wait" => {
+                        let mutex = args[1];
+                        if MUTEXES.get(&mutex).map_or(false, |m| m.0 == Some(r.tid)) {       // mutex를 풀고 기다림 (깨어나면 다시 잡음)
+                            mutex_release(mutex);
+                            CONDS.entry(id).or_insert(VecDeque::new()).push_back((r.tid, mutex));
+                            block(r, Wait::Cond(id));
+                        }
+                        else {
+                            sync_event(format!("cond_wait failed: {} does not hold mutex {}", me, mutex));
+                            make_ready(r);
+                        }
+                    }
+                    _ => {      // cond_signal
+                        match CONDS.entry(id).or_insert(VecDeque::new()).pop_front() {
+                            None => sync_event(format!("cond {} signalled with no waiter", id)),
+                            Some((t, mutex)) => cond_wake(id, t, mutex),
+                        }
+                        make_ready(r);
+                    }
+                }
+            }
+        }
+        CYCLE_DONE = true;
+        print_cycle();
+
+        // 3. 세 번째 cycle
+        idle_or_schedule();     // scheduling
+        return;
+    }
+}
+
+// mutex를 풀고 wait queue의 맨 앞 thread에 넘겨줌
+fn mutex_release(id: u32) {
+    unsafe {
+        let m = MUTEXES.get_mut(&id).unwrap();
+        m.0 = m.1.pop_front();
+        if let Some(t) = m.0 {
+            sync_event(format!("mutex {} -> {}", id, tid_label(t)));
+            wake(t, Wait::Mutex(id));
+        }
+    }
+}
+
+// lock을 풀고 기다리는 thread 중 실제 우선순위가 가장 높은 thread(같으면 먼저 기다린 thread)에 넘겨줌
+fn lock_release(id: u32) {
+    unsafe {
+        let l = LOCKS.get_mut(&id).unwrap();
+        let next = (0..l.1.len()).max_by_key(|&i| (effective_priority(l.1[i]), std::cmp::Reverse(i)));
+        l.0 = next.and_then(|i| l.1.remove(i));
+        if let Some(t) = l.0 {
+            sync_event(format!("lock {} -> {}", id, tid_label(t)));
+            wake(t, Wait::Lock(id));
+        }
+    }
+}
+
+// lock의 ceiling 우선순위 - 지정되지 않았으면 프로그램에 lock id가 있는 thread 중 가장 높은 기본 우선순위
+fn lock_ceiling(id: u32) -> u32 {
+    unsafe {
+        if let Some(&c) = CEILINGS.get(&id) {return c;}
+        let line = format!("lock {}", id);
+        PROCS.keys().filter_map(|&t| find_thread(t)).filter(|p| p.code.iter().any(|l| l.trim() == line)).map(|p| p.priority).max().unwrap_or(1)
+    }
+}
+
+// 스케줄링에 쓰는 실제 우선순위 - inherit면 가진 lock을 기다리는 thread의 우선순위를 물려받고 (기다리는 thread가 물려받은 것까지),
+// ceiling이면 lock을 가지고 있는 동안 lock의 ceiling까지 올라감
+fn effective_priority(tid: u32) -> u32 {
+    fn boost(tid: u32, visited: &mut Vec<u32>) -> u32 {
+        unsafe {
+            visited.push(tid);
+            let mut priority = find_thread(tid).map_or(1, |p| p.priority);
+            for (&id, l) in LOCKS.iter() {
+                if l.0 != Some(tid) {continue;}
+                if LOCK_PROTOCOL == "ceiling" {priority = priority.max(lock_ceiling(id));}
+                else if LOCK_PROTOCOL == "inherit" {
+                    for &w in l.1.iter() {
+                        if !visited.contains(&w) {priority = priority.max(boost(w, visited));}       // deadlock으로 서로 기다리는 경우 한 번만
+                    }
+                }
+            }
+            priority
+        }
+    }
+    boost(tid, &mut Vec::new())
+}
+
+// ready queue에서 다음에 실행할 process 꺼내기 - sched=priority면 실제 우선순위가 가장 높은 process (같으면 먼저 들어온 process)
+fn next_ready() -> Option<Process> {
+    unsafe {
+        if SCHED != "priority" {return RQ.pop_front();}
+        let i = (0..RQ.len()).max_by_key(|&i| (effective_priority(RQ[i].tid), std::cmp::Reverse(i)))?;
+        RQ.remove(i)
+    }
+}
+
+// condition variable에서 깨운 thread가 mutex를 다시 잡음 -> 다른 thread가 가지고 있으면 mutex의 wait queue로 옮겨 계속 기다림
+fn cond_wake(id: u32, tid: u32, mutex: u32) {
+    unsafe {
+        let m = MUTEXES.get_mut(&mutex).unwrap();
+        if m.0.is_none() {
+            m.0 = Some(tid);
+            sync_event(format!("cond {} -> {}", id, tid_label(tid)));
+            wake(tid, Wait::Cond(id));
+        }
+        else {
+            m.1.push_back(tid);
+            sync_event(format!("cond {} -> {} (waits for mutex {})", id, tid_label(tid), mutex));
+            match state_of(tid) {
+                State::SuspendedBlocked(_) => set_state(tid, State::SuspendedBlocked(Wait::Mutex(mutex))),
+                _ => set_state(tid, State::Blocked(Wait::Mutex(mutex))),
+            }
+        }
+    }
+}
+
+// 종료되는 thread를 모든 동기화 객체의 wait queue에서 빼고, 가지고 있던 mutex / lock은 다음 thread에 넘겨줌
+fn sync_cleanup(tid: u32) {
+    unsafe {
+        sync_unwait(tid);
+        let held: Vec<u32> = MUTEXES.iter().filter(|(_, m)| m.0 == Some(tid)).map(|(id, _)| *id).collect();
+        for id in held {
+            mutex_release(id);
+        }
+        let held: Vec<u32> = LOCKS.iter().filter(|(_, l)| l.0 == Some(tid)).map(|(id, _)| *id).collect();
+        for id in held {
+            lock_release(id);
+        }
+    }
+}
+
+// thread를 모든 동기화 객체의 wait queue에서 뺌
+fn sync_unwait(tid: u32) {
+    unsafe {
+        for m in MUTEXES.values_mut() {m.1.retain(|&t| t != tid);}
+        for sem in SEMAPHORES.values_mut() {sem.1.retain(|&t| t != tid);}
+        for q in CONDS.values_mut() {q.retain(|w| w.0 != tid);}
+        for l in LOCKS.values_mut() {l.1.retain(|&t| t != tid);}
+    }
+}
+
+// 이번 cycle의 동기화 결과 추가
+fn sync_event(event: String) {
+    unsafe {
+        if !SYNC_EVENT.is_empty() {SYNC_EVENT.push_str(", ");}
+        SYNC_EVENT.push_str(&event);
+    }
+}
+
+// 명령어 request / free 처리 -> 자원을 얻지 못하면 waiting (Banker's algorithm이면 불안전한 요청도 waiting)
+fn resource_call(order: &str, res: String, n: u32) {
+    unsafe {
+        // 1. 첫 번째 cycle 출력
+        CYCLE += 1;
+        COMMAND = format!("{order} {res} {n}");
+        CYCLE_DONE = true;
+        print_cycle();
+        MODE = String::from("kernel");      // 모드 스위칭
+
+        // 2. 두 번째 cycle
+        CYCLE += 1;
+        COMMAND = String::from("system call");
+        match RUNNING.take() {
+            None => return,
+            Some(r) => {
+                let me = thread_label(r.pid, r.tid);
+                let held = HELD.get(&r.tid).and_then(|h| h.get(&res)).copied().unwrap_or(0);
+                let claim = CLAIMS.get(&r.tid).and_then(|c| c.get(&res)).copied().unwrap_or(0);
+                match (order, RESOURCES.get(&res).copied()) {
+                    (_, None) => {
+                        resource_event(format!("{} failed: no resource {}", order, res));
+                        make_ready(r);
+                    }
+                    ("request", Some((total, _))) if held + n > total => {
+                        resource_event(format!("request failed: {} {} > {} total", res, held + n, total));
+                        make_ready(r);
+                    }
+                    ("request", Some(_)) if BANKER && held + n > claim => {       // 선언한 claim을 넘는 요청은 거부
+                        resource_event(format!("request failed: {} exceeds claim {} {}", me, res, claim));
+                        make_ready(r);
+                    }
+                    ("request", Some((_, free))) => {
+                        if can_grant(r.tid, &res, n) {
+                            grant(r.tid, &res, n);
+                            make_ready(r);
+                        }
+                        else {      // 자원이 모자라거나 들어주면 불안전한 상태가 됨 -> 자원이 반환될 때까지 waiting
+                            if n <= free {resource_event(format!("{} {} for {} unsafe", res, n, me));}
+                            RES_WAIT.push_back((r.tid, res, n));
+                            block(r, Wait::Resource);
+                        }
+                    }
+                    (_, Some(_)) if n > held => {
+                        resource_event(format!("free failed: {} holds {} {}", me, res, held));
+                        make_ready(r);
+                    }
+                    (_, Some(_)) => {
+                        release_resource(r.tid, &res, n);
+                        grant_waiters();        // 반환된 자원으로 기다리던 요청 처리
+                        make_ready(r);
+                    }
+                }
+            }
+        }
+        CYCLE_DONE = true;
+        print_cycle();
+
+        // 3. 세 번째 cycle
+        idle_or_schedule();     // scheduling
+        return;
+    }
+}
+
+// Banker's algorithm admission: claim 선언이 자원의 전체 개수를 넘는 프로그램은 끝까지 실행될 수 없으므로 거부 (resource event로 출력)
+fn admit_claims(name: &str, code: &VecDeque<String>) -> bool {
+    unsafe {
+        if !BANKER {return true;}
+        for line in code {
+            let args: Vec<&str> = line.trim().split(" ").collect();
+            if args[0] != "claim" || args.len() < 3 {continue;}
+            if let (Some(&(total, _)), Ok(n)) = (RESOURCES.get(args[1]), args[2].parse::<u32>()) {
+                if n > total {
+                    resource_event(format!("{} rejected: claim {} {} exceeds total {}", name, args[1], n, total));
+                    return false;
+                }
+            }
+        }
+        true
+    }
+}
+
+// 프로그램 파일의 claim 선언(claim 자원 개수)을 읽어 Banker's algorithm의 최대 요청으로 등록 (없는 자원은 무시)
+fn register_claims<'a>(tid: u32, code: impl IntoIterator<Item = &'a String>) {
+    unsafe {
+        if !BANKER {return;}
+        let mut claims: BTreeMap<String, u32> = BTreeMap::new();
+        for line in code {
+            let args: Vec<&str> = line.trim().split(" ").collect();
+            if args[0] != "claim" || args.len() < 3 {continue;}
+            if let (Some(_), Ok(n)) = (RESOURCES.get(args[1]), args[2].parse::<u32>()) {
+                claims.insert(args[1].to_string(), n);
+            }
+        }
+        if !claims.is_empty() {CLAIMS.insert(tid, claims);}
+    }
+}
+
+// 요청을 지금 들어줄 수 있는지 (남은 개수가 충분하고, Banker's algorithm이면 들어준 뒤에도 안전한 상태)
+fn can_grant(tid: u32, res: &str, n: u32) -> bool {
+    unsafe {
+        n <= RESOURCES[res].1 && (!BANKER || banker_safe(tid, res, n))
+    }
+}
+
+// Banker's algorithm: tid에게 자원을 n개 더 주었다고 가정했을 때 모든 thread가 claim까지 요청해도 끝날 수 있는 순서가 있는지
+fn banker_safe(tid: u32, res: &str, n: u32) -> bool {
+    unsafe {
+        let mut work: BTreeMap<String, u32> = RESOURCES.iter().map(|(r, v)| (r.clone(), v.1)).collect();
+        *work.get_mut(res).unwrap() -= n;
+        let mut threads: Vec<(BTreeMap<String, u32>, BTreeMap<String, u32>)> = Vec::new();      // (더 필요한 개수, 가지고 있는 개수)
+        for (&t, claim) in CLAIMS.iter() {
+            let mut held = HELD.get(&t).cloned().unwrap_or_default();
+            if t == tid {*held.entry(res.to_string()).or_insert(0) += n;}
+            let need = claim.iter().map(|(r, c)| (r.clone(), c.saturating_sub(held.get(r).copied().unwrap_or(0)))).collect();
+            threads.push((need, held));
+        }
+        // 필요한 만큼 줄 수 있는 thread가 끝나고 자원을 돌려준다고 보고 반복
+        while let Some(i) = threads.iter().position(|(need, _)| need.iter().all(|(r, k)| *k <= work[r])) {
+            for (r, k) in threads.remove(i).1 {
+                *work.get_mut(&r).unwrap() += k;
+            }
+        }
+        threads.is_empty()
+    }
+}
+
+// 자원 n개를 tid에 할당
+fn grant(tid: u32, res: &str, n: u32) {
+    unsafe {
+        RESOURCES.get_mut(res).unwrap().1 -= n;
+        *HELD.entry(tid).or_insert(BTreeMap::new()).entry(res.to_string()).or_insert(0) += n;
+    }
+}
+
+// tid가 가진 자원 n개를 반환
+fn release_resource(tid: u32, res: &str, n: u32) {
+    unsafe {
+        RESOURCES.get_mut(res).unwrap().1 += n;
+        let held = HELD.get_mut(&tid).unwrap();
+        *held.get_mut(res).unwrap() -= n;
+        if held[res] == 0 {held.remove(res);}
+        if held.is_empty() {HELD.remove(&tid);}
+    }
+}
+
+// 기다리는 요청 중 지금 들어줄 수 있는 것을 요청 순서대로 처리하고 해당 thread 깨우기
+fn grant_waiters() {
+    unsafe {
+        while let Some(i) = RES_WAIT.iter().position(|(t, res, n)| can_grant(*t, res, *n)) {
+            let (t, res, n) = RES_WAIT.remove(i).unwrap();
+            grant(t, &res, n);
+            resource_event(format!("{} {} -> {}", res, n, tid_label(t)));
+            wake(t, Wait::Resource);
+        }
+    }
+}
+
+// 종료되는 thread가 가진 자원을 모두 반환하고 요청 / claim 취소
+fn resource_cleanup(tid: u32) {
+    unsafe {
+        RES_WAIT.retain(|w| w.0 != tid);
+        CLAIMS.remove(&tid);
+        if let Some(held) = HELD.remove(&tid) {
+            for (res, k) in held {
+                RESOURCES.get_mut(&res).unwrap().1 += k;
+            }
+        }
+        grant_waiters();
+    }
+}
+
+// 이번 cycle의 자원 요청 / 반환 결과 추가
+fn resource_event(event: String) {
+    unsafe {
+        if !RESOURCE_EVENT.is_empty() {RESOURCE_EVENT.push_str(", ");}
+        RESOURCE_EVENT.push_str(&event);
+    }
+}
+
+// 자원 할당 그래프를 줄여가며 deadlock에 빠진 thread와 기다리는 자원 반환 (자원과 mutex, lock 모두 대상, mutex와 lock은 개수 1인 자원)
+// 요청이 남은 자원으로 채워질 수 있는(또는 자원을 기다리지 않는) thread는 끝날 수 있다고 보고 가진 자원을 돌려놓음 -> 끝까지 남는 thread가 deadlock
+fn find_deadlock() -> Vec<(u32, String)> {
+    unsafe {
+        let mut work: BTreeMap<String, u32> = RESOURCES.iter().map(|(r, v)| (r.clone(), v.1)).collect();
+        for (id, m) in MUTEXES.iter() {
+            work.insert(format!("mutex {}", id), if m.0.is_none() {1} else {0});
+        }
+        for (id, l) in LOCKS.iter() {
+            work.insert(format!("lock {}", id), if l.0.is_none() {1} else {0});
+        }
+        let mut nodes: Vec<(u32, Option<(String, u32)>, BTreeMap<String, u32>)> = Vec::new();       // (tid, 기다리는 자원과 개수, 가진 자원)
+        for (&tid, e) in PROCS.iter() {
+            if matches!(e.state, State::Zombie(_) | State::Terminated) {continue;}
+            let mut held = HELD.get(&tid).cloned().unwrap_or_default();
+            for (id, m) in MUTEXES.iter() {
+                if m.0 == Some(tid) {held.insert(format!("mutex {}", id), 1);}
+            }
+            for (id, l) in LOCKS.iter() {
+                if l.0 == Some(tid) {held.insert(format!("lock {}", id), 1);}
+            }
+            let request = match e.state {
+                State::Blocked(Wait::Mutex(id)) | State::SuspendedBlocked(Wait::Mutex(id)) => Some((format!("mutex {}", id), 1)),
+                State::Blocked(Wait::Lock(id)) | State::SuspendedBlocked(Wait::Lock(id)) => Some((format!("lock {}", id), 1)),
+                _ => RES_WAIT.iter().find(|w| w.0 == tid).map(|w| (w.1.clone(), w.2)),
+            };
+            nodes.push((tid, request, held));
+        }
+        while let Some(i) = nodes.iter().position(|(_, request, _)| request.as_ref().map_or(true, |(r, n)| *n <= work[r])) {
+            for (r, k) in nodes.remove(i).2 {
+                *work.get_mut(&r).unwrap() += k;
+            }
+        }
+        nodes.into_iter().map(|(tid, request, _)| (tid, request.unwrap().0)).collect()
+    }
+}
+
+// 자원(또는 mutex, lock)을 가지고 있는 thread들
+fn holders(res: &str) -> Vec<u32> {
+    unsafe {
+        if let Some(id) = res.strip_prefix("mutex ") {
+            return MUTEXES[&id.parse::<u32>().unwrap()].0.into_iter().collect();
+        }
+        if let Some(id) = res.strip_prefix("lock ") {
+            return LOCKS[&id.parse::<u32>().unwrap()].0.into_iter().collect();
+        }
+        HELD.iter().filter(|(_, h)| h.contains_key(res)).map(|(t, _)| *t).collect()
+    }
+}
+
+// 자원 할당 그래프에서 deadlock을 찾아 wait-for 관계를 저장 -> 새로 생긴 deadlock은 기록
+fn detect_deadlock() {
+    unsafe {
+        let deadlocked = find_deadlock();
+        let tids: Vec<u32> = deadlocked.iter().map(|d| d.0).collect();
+        if !tids.is_empty() && DEADLOCKED.is_empty() {      // 새로 생긴 deadlock 기록
+            let labels: Vec<String> = tids.iter().map(|t| tid_label(*t)).collect();
+            DEADLOCKS.push(format!("cycle #{}: {}", CYCLE, labels.join(" ")));
+        }
+        DEADLOCKED = deadlocked.iter().map(|(t, res)| {
+            let to: Vec<String> = holders(res).into_iter().filter(|h| tids.contains(h)).map(tid_label).collect();
+            format!("{} -> {} ({})", tid_label(*t), to.join(" "), res)
+        }).collect();
+    }
+}
+
+// deadlock 회복: abort_one은 가장 늦게 만들어진 thread부터 하나씩 종료, abort_all은 deadlock에 빠진 thread 모두 종료,
+// preempt는 자원을 가진 thread 중 가장 늦게 만들어진 thread의 자원을 빼앗고 다시 요청하게 함 (자원이 없으면 종료)
+fn deadlock_recovery() {
+    unsafe {
+        if RECOVERY.is_empty() {return;}
+        if !DEADLOCKED.is_empty() {RECOVERED = DEADLOCKED.join(", ");}
+        loop {
+            let deadlocked = find_deadlock();
+            if deadlocked.is_empty() {
+                DEADLOCKED.clear();     // 회복된 deadlock은 더 이상 출력하지 않음
+                return;
+            }
+            let tids: Vec<u32> = deadlocked.iter().map(|d| d.0).collect();
+            let victim = *tids.iter().max().unwrap();
+            let holding = tids.iter().filter(|t| HELD.contains_key(t)).max().copied();
+            match (RECOVERY.as_str(), holding) {
+                ("abort_all", _) => {
+                    for t in tids.into_iter().rev() {
+                        if !matches!(state_of(t), State::Zombie(_) | State::Terminated) {abort_thread(t);}       // main thread와 함께 이미 종료된 thread는 건너뜀
+                    }
+                }
+                ("preempt", Some(t)) => preempt_resources(t),
+                _ => abort_thread(victim),
+            }
+        }
+    }
+}
+
+// deadlock에 빠진 thread 종료 (waiting queue 또는 suspended queue에 있음) - SIGKILL로 종료시킨 것으로 취급
+fn abort_thread(tid: u32) {
+    unsafe {
+        let victim = if let Some(i) = WQ.iter().position(|p| p.tid == tid) {WQ.remove(i)}
+            else if let Some(i) = SQ.iter().position(|p| p.tid == tid) {SQ.remove(i)}
+            else if let Some(i) = RQ.iter().position(|p| p.tid == tid) {RQ.remove(i)}      // abort_all에서 앞의 thread가 종료되어 깨어난 thread
+            else {None};
+        if victim.is_none() {return;}
+        deadlock_event(format!("abort {}", tid_label(tid)));
+        RUNNING = victim;
+        terminate_running(128 + SIGKILL as i32, true);
+    }
+}
+
+// thread가 가진 자원을 모두 빼앗음 -> 나중에 다시 얻도록 남은 명령어 앞에 request를 넣음
+fn preempt_resources(tid: u32) {
+    unsafe {
+        let held = HELD.remove(&tid).unwrap();
+        let p = find_thread(tid).unwrap();
+        for (res, k) in held.into_iter().rev() {
+            RESOURCES.get_mut(&res).unwrap().1 += k;
+            p.lines.push_front(format!("request {} {}", res, k));
+            deadlock_event(format!("preempt {} {} from {}", res, k, tid_label(tid)));
+        }
+        grant_waiters();
+    }
+}
+
+// 이번 cycle의 deadlock 회복 내용 추가 (발생한 deadlock 기록에도 남김)
+fn deadlock_event(event: String) {
+    unsafe {
+        if !DEADLOCK_EVENT.is_empty() {DEADLOCK_EVENT.push_str(", ");}
+        DEADLOCK_EVENT.push_str(&event);
+        if let Some(last) = DEADLOCKS.last_mut() {
+            last.push_str(&format!(", {}", event));
+        }
+    }
+}
+
+// 프로그램 파일 읽고 명령어에 맞게 처리하는 함수
+fn running_process() {
+    unsafe{
+        handle_interrupts();        // 다음 명령어 실행 전에 끝난 장치 I/O의 interrupt 처리
+        if deliver_signals() {return;}      // user mode로 돌아가기 전에 signal 전달 (종료 / 정지되면 다음 process가 이미 스케줄됨)
+        match &RUNNING {
+            None => return,
+            Some(p) => {
+                let mut v = p.lines.clone();
+                while !v.is_empty() {
+                    MODE = String::from("user");
+                    let order = v.pop_front().unwrap();
+                    if order.trim().ends_with(':') || order.trim() == "return" || order.trim().starts_with("claim ") {continue;}       // handler label과 handler 끝, 자원 claim 선언은 명령어가 아님
+                    // running process의 요소 lines를 이후 남은 명령어들의 queue로 갱신해주기
+                    let mut new_lines: VecDeque<String> = VecDeque::new();
+                    for after in &v {
+                        new_lines.push_back(after.to_string());
+                    }
+                    let mut c = p.clone();
+                    c.lines = new_lines;
+                    RUNNING = Some(c);
+                    // 명령어 이름(첫 단어)으로 처리할 함수 결정
+                    let args: Vec<&str> = order.split_whitespace().collect();
+                    match args.first().copied().unwrap_or("") {
+                        "memory_allocate" => {      // 명령어 memory_allocate이 들어왔을 경우
+                            let n: u32 = args.last().unwrap().parse().unwrap();
+                            memory_allocate(n);
+                        }
+                        "memory_release" => {        // 명령어 memory_release가 들어왔을 경우
+                            let n: u32 = args.last().unwrap().parse().unwrap();
+                            memory_release(n);
+                        }
+                        "memory_read" => {      // 명령어 memory_read가 들어왔을 경우
+                            let n: u32 = args.last().unwrap().parse().unwrap();
+                            memory_read(n);
+                        }
+                        "memory_write" => {      // 명령어 memory_write가 들어왔을 경우
+                            let n: u32 = args.last().unwrap().parse().unwrap();
+                            memory_write(n);
+                        }
+                        "memory_lock" => {      // 명령어 memory_lock이 들어왔을 경우
+                            let n: u32 = args.last().unwrap().parse().unwrap();
+                            memory_lock(n);
+                        }
+                        "memory_unlock" => {      // 명령어 memory_unlock이 들어왔을 경우
+                            let n: u32 = args.last().unwrap().parse().unwrap();
+                            memory_unlock(n);
+                        }
+                        "mprotect" => {      // 명령어 mprotect가 들어왔을 경우
+                            let n: u32 = args[1].parse().unwrap();
+                            match parse_perms(args.last().unwrap()) {
+                                None => println!("wrong order!"),
+                                Some(perms) => mprotect(n, perms),
+                            }
+                        }
+                        "shm_create" => {      // 명령어 shm_create가 들어왔을 경우
+                            let key: u32 = args[1].parse().unwrap();
+                            let n: u32 = args.last().unwrap().parse().unwrap();
+                            shm_create(key, n);
+                        }
+                        "shm_attach" => {      // 명령어 shm_attach가 들어왔을 경우
+                            let key: u32 = args.last().unwrap().parse().unwrap();
+                            shm_attach(key);
+                        }
+                        "shm_detach" => {      // 명령어 shm_detach가 들어왔을 경우
+                            let key: u32 = args.last().unwrap().parse().unwrap();
+                            shm_detach(key);
+                        }
+                        "file_read" | "file_write" => {      // 명령어 file_read / file_write가 들어왔을 경우
+                            let block: u32 = args.last().unwrap().parse().unwrap();
+                            file_access(args[1].to_string(), block, args[0] == "file_write");
+                        }
+                        "munmap" => {      // 명령어 munmap이 들어왔을 경우
+                            munmap(args.last().unwrap().to_string());
+                        }
+                        "mmap" => {      // 명령어 mmap이 들어왔을 경우
+                            let n: u32 = args.last().unwrap().parse().unwrap();
+                            mmap(args[1].to_string(), n);
+                        }
+                        "open" | "close" | "read" | "write" | "mkdir" | "unlink" => {      // 파일시스템 명령어가 들어왔을 경우
+                            fs_call(args.iter().map(|a| a.to_string()).collect());
+                        }
+                        "sleep" | "gettime" | "alarm" => {      // timer 명령어가 들어왔을 경우
+                            let n: u32 = if args.len() > 1 {args[1].parse().unwrap()} else {0};
+                            timer_call(args[0], n);
+                        }
+                        "mutex_lock" | "mutex_unlock" | "sem_init" | "sem_wait" | "sem_post" | "cond_wait" | "cond_signal" | "lock" | "unlock" => {      // 동기화 명령어가 들어왔을 경우
+                            let ids: Vec<u32> = args[1..].iter().map(|a| a.parse().unwrap()).collect();
+                            sync_call(args[0], ids);
+                        }
+                        "request" | "free" => {      // 명령어 request / free가 들어왔을 경우
+                            let n: u32 = args.last().unwrap().parse().unwrap();
+                            resource_call(args[0], args[1].to_string(), n);
+                        }
+                        "kill" => {      // 명령어 kill이 들어왔을 경우
+                            match (args[1].parse::<u32>(), parse_signal(args.last().unwrap())) {
+                                (Ok(pid), Some(sig)) => kill(pid, sig),
+                                _ => println!("wrong order!"),
+                            }
+                        }
+                        "signal" => {      // 명령어 signal이 들어왔을 경우
+                            match parse_signal(args[1]) {
+                                Some(sig) => signal(sig, args.last().unwrap().to_string()),
+                                None => println!("wrong order!"),
+                            }
+                        }
+                        "thread_create" => {      // 명령어 thread_create가 들어왔을 경우
+                            thread_create(args.last().unwrap().to_string());
+                        }
+                        "thread_join" => {      // 명령어 thread_join이 들어왔을 경우
+                            let tid: u32 = args.last().unwrap().parse().unwrap();
+                            thread_join(tid);
+                        }
+                        "io" => {      // 명령어 io가 들어왔을 경우
+                            let n: u32 = args.last().unwrap().parse().unwrap();
+                            io(args[1].to_string(), n);
+                        }
+                        "fork_and_exec" => {       // 명령어 fork가 들어왔을 경우
+                            fork_and_exec(args.last().unwrap().to_string());
+                        }
+                        "wait" => wait(),      // 명령어 wait가 들어왔을 경우
+                        "exit" => {      // 명령어 exit가 들어왔을 경우
+                            match args.get(1).map_or(Ok(0), |c| c.parse::<i32>()) {
+                                Ok(code) => exit(code),
+                                Err(_) => println!("wrong order!"),     // exit status는 정수만
+                            }
+                        }
+                        _ => println!("wrong order!"),
+                    }
+                    return;
+                }
+            }
+        }
+    }
+}
+
+// 실행 인자로 들어온 추가 옵션(key=value)을 읽고 해당 설정 저장
+fn set_option(arg: &str) {
+    unsafe {
+        let (key, value) = match arg.split_once('=') {
+            None => {
+                println!("wrong option: {arg}");
+                return;
+            }
+            Some(kv) => kv,
+        };
+        match key {
+            "pt_level" => {     // 페이지 테이블 단계 수 (1 ~ 3)
+                PT_LEVEL = value.parse().unwrap_or(0);
+                if PT_LEVEL < 1 || PT_LEVEL > 3 {
+                    println!("wrong option: {arg}");
+                    process::exit(1);
+                }
+            }
+            "translation" => {      // 주소 변환 구조 (page_table / inverted / hashed)
+                if value == "page_table" {return;}
+                if value != "inverted" && value != "hashed" {
+                    println!("wrong option: {arg}");
+                    process::exit(1);
+                }
+                TRANSLATION = value.to_string();
+                HPT = vec![Vec::new(); HPT_SIZE];
+            }
+            "frame_alloc" => {      // 프레임 할당 방식 (equal / proportional / priority)
+                if value != "equal" && value != "proportional" && value != "priority" {
+                    println!("wrong option: {arg}");
+                    process::exit(1);
+                }
+                FRAME_ALLOC = value.to_string();
+            }
+            "replacement" => {      // 페이지 교체 범위 (global / local)
+                if value != "global" && value != "local" {
+                    println!("wrong option: {arg}");
+                    process::exit(1);
+                }
+                REPLACEMENT = value.to_string();
+            }
+            "priority" => {     // 프로그램별 우선순위 (program:n)
+                match value.split_once(':').map(|(name, n)| (name, n.parse::<u32>())) {
+                    Some((name, Ok(n))) => {PRIORITY.insert(name.to_string(), n);}
+                    _ => {
+                        println!("wrong option: {arg}");
+                        process::exit(1);
+                    }
+                }
+            }
+            "ws_window" => {        // working set window 크기 (메모리 참조 횟수)
+                match value.parse() {
+                    Ok(n) => WS_WINDOW = n,
+                    Err(_) => {
+                        println!("wrong option: {arg}");
+                        process::exit(1);
+                    }
+                }
+            }
+            "swap_low" => {     // process 전체를 swap out하는 빈 프레임 수 기준
+                match value.parse() {
+                    Ok(n) => SWAP_LOW = n,
+                    Err(_) => {
+                        println!("wrong option: {arg}");
+                        process::exit(1);
+                    }
+                }
+            }
+            "swap_high" => {        // suspend된 process를 swap in하는 빈 프레임 수 기준
+                match value.parse() {
+                    Ok(n) => SWAP_HIGH = n,
+                    Err(_) => {
+                        println!("wrong option: {arg}");
+                        process::exit(1);
+                    }
+                }
+            }
+            "swap_policy" => {      // swap out할 process 선택 방식 (blocked / largest / priority / youngest)
+                if value != "blocked" && value != "largest" && value != "priority" && value != "youngest" {
+                    println!("wrong option: {arg}");
+                    process::exit(1);
+                }
+                SWAP_POLICY = value.to_string();
+            }
+            "kswapd_low" => {       // kswapd가 깨어나는 빈 프레임 수 기준
+                match value.parse() {
+                    Ok(n) => KSWAPD_LOW = n,
+                    Err(_) => {
+                        println!("wrong option: {arg}");
+                        process::exit(1);
+                    }
+                }
+            }
+            "kswapd_high" => {      // kswapd가 회수를 멈추는 빈 프레임 수 기준
+                match value.parse() {
+                    Ok(n) => KSWAPD_HIGH = n,
+                    Err(_) => {
+                        println!("wrong option: {arg}");
+                        process::exit(1);
+                    }
+                }
+            }
+            "swap_size" => {        // swap 공간 크기 (페이지 수)
+                match value.parse() {
+                    Ok(n) => SWAP_SIZE = n,
+                    Err(_) => {
+                        println!("wrong option: {arg}");
+                        process::exit(1);
+                    }
+                }
+            }
+            "disk" => {     // 디스크 스케줄링 알고리즘 (fcfs / sstf / scan / cscan / look)
+                if !["fcfs", "sstf", "scan", "cscan", "look"].contains(&value) {
+                    println!("wrong option: {arg}");
+                    process::exit(1);
+                }
+                DISK_SCHED = value.to_string();
+            }
+            "pstree" => {       // process tree 출력 여부 (on / off)
+                if value != "on" && value != "off" {
+                    println!("wrong option: {arg}");
+                    process::exit(1);
+                }
+                PSTREE = value == "on";
+            }
+            "sched" => {        // CPU 스케줄링 방식 (fifo / priority)
+                if value != "fifo" && value != "priority" {
+                    println!("wrong option: {arg}");
+                    process::exit(1);
+                }
+                if value == "priority" {SCHED = value.to_string();}
+            }
+            "lock_protocol" => {        // lock의 우선순위 프로토콜 (none / inherit / ceiling)
+                if !["none", "inherit", "ceiling"].contains(&value) {
+                    println!("wrong option: {arg}");
+                    process::exit(1);
+                }
+                if value != "none" {LOCK_PROTOCOL = value.to_string();}
+            }
+            "ceiling" => {      // lock별 ceiling 우선순위 (lock id:n)
+                match value.split_once(':').map(|(id, n)| (id.parse::<u32>(), n.parse::<u32>())) {
+                    Some((Ok(id), Ok(n))) => {CEILINGS.insert(id, n);}
+                    _ => {
+                        println!("wrong option: {arg}");
+                        process::exit(1);
+                    }
+                }
+            }
+            "resources" => {        // 자원 종류와 개수 (이름:개수,이름:개수...)
+                for item in value.split(',') {
+                    match item.split_once(':').map(|(name, n)| (name, n.parse::<u32>())) {
+                        Some((name, Ok(n))) if !name.is_empty() => {RESOURCES.insert(name.to_string(), (n, n));}
+                        _ => {
+                            println!("wrong option: {arg}");
+                            process::exit(1);
+                        }
+                    }
+                }
+            }
+            "banker" => {       // Banker's algorithm 사용 여부 (on / off)
+                if value != "on" && value != "off" {
+                    println!("wrong option: {arg}");
+                    process::exit(1);
+                }
+                BANKER = value == "on";
+            }
+            "recovery" => {     // deadlock 회복 방법 (none / abort_one / abort_all / preempt)
+                if !["none", "abort_one", "abort_all", "preempt"].contains(&value) {
+                    println!("wrong option: {arg}");
+                    process::exit(1);
+                }
+                if value != "none" {RECOVERY = value.to_string();}
+            }
+            "fs_image" => {     // 파일시스템 이미지 파일 경로
+                FS_IMAGE = value.to_string();
+            }
+            _ => println!("wrong option: {arg}"),
+        }
+    }
+}
+
+fn main() {     
+    unsafe{
+        let args: Vec<String> = env::args().collect();
+        INPUT_DIR = String::from(&args[1]);    // input file들이 있는 폴더 경로 저장 
+        CHANGE_ALGORITHM = String::from(&args[2]);      // 페이지 교체 알고리즘 저장
+        for arg in args.iter().skip(3) {
+            set_option(arg);        // 추가 옵션 저장 (key=value 형식)
+        }
+        if !FRAME_ALLOC.is_empty() && REPLACEMENT.is_empty() {
+            REPLACEMENT = String::from("global");
+        }
+        if REPLACEMENT.contains("local") && FRAME_ALLOC.is_empty() {       // 지역 교체는 할당량이 필요하므로 기본은 균등 분배
+            FRAME_ALLOC = String::from("equal");
+        }
+        if SWAP_LOW > 0 && SWAP_HIGH < SWAP_LOW {      // high watermark 기본값은 low의 2배
+            SWAP_HIGH = (SWAP_LOW * 2).min(16);
+        }
+        if KSWAPD_LOW > 0 && KSWAPD_HIGH < KSWAPD_LOW {        // high watermark 기본값은 low의 2배
+            KSWAPD_HIGH = (KSWAPD_LOW * 2).min(16);
+        }
+        if PT_LEVEL > 1 && !TRANSLATION.is_empty() {       // 다단계 페이지 테이블은 프로세스별 페이지 테이블에서만 사용 가능
+            println!("pt_level cannot be used with translation={}", TRANSLATION);
+            process::exit(1);
+        }
+
+        fs_load();      // 파일시스템 초기화 (이미지 파일이 있으면 불러오기)
+
         // cycle #0
         // init 생성 
         let process_dir: String = format!("{}\\{}", INPUT_DIR, "init").to_string();    
@@ -1039,26 +4786,25 @@ fn main() {
         }
         let virtual_mem: [Option<Page>;32] = [NONE_PAGE;32];    
         let page_table: [[i32;2];32] = [[-1;2];32];     
-        let init = create_process("init".to_string(), PID, 0, "None".to_string(), lines, virtual_mem, page_table, -1, -1);
+        if admit_claims("init", &lines) {       // claim 때문에 거부되면 실행할 process 없이 종료
+            NEWP = Some(create_process("init".to_string(), PID, 0, lines, virtual_mem, page_table, -1, -1));
+        }
         MODE = String::from("kernel");
         COMMAND = String::from("boot");
-        NEWP = Some(init);
         CYCLE_DONE = true;
         print_cycle();
 
         // cycle #1
             // new process -> ready queue
-        match &NEWP {
-            None => return,
-            Some(p) => {
-                RQ.push_back(p.clone());
-                NEWP = None;
-            }
-        }
-        idle_or_schedule();     // ready -> running
+        if let Some(p) = NEWP.take() {
+            make_ready(p);
+            idle_or_schedule();     // ready -> running
 
-        // cycle #2~ 
-        running_process();
+            // cycle #2~ 
+            running_process();
+        }
+        print_summary();
+        fs_save();
 
         let mut result = std::fs::File::create("result").expect("create failed");
         result.write_all(CYCLE_INFO.as_bytes()).expect("write failed");